@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+use std::path::PathBuf;
+
+use crate::{Error, InputLoader, Result};
+
+/// Environment variable that overrides where the answers file lives
+pub const ANSWERS_FILE_VAR: &str = "ADVENT_ANSWERS_FILE";
+/// Name of the answers file, kept next to the personal inputs
+pub const ANSWERS_FILE: &str = "answers.txt";
+
+/// Recorded answers, keyed by day and part.
+///
+/// Stored as plain text, one `<day> <part> <answer>` per line, with `#` comments
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    data: BTreeMap<(u8, u8), String>,
+}
+
+/// Outcome of comparing a solution to its recorded answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: String },
+    Missing,
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut data = BTreeMap::new();
+        for (line_idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.splitn(3, char::is_whitespace);
+            let (Some(day), Some(part), Some(answer)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                let message = "expected <day> <part> <answer>";
+                return Err(Error::parse(message).at_line(line_idx));
+            };
+            let day = day
+                .parse()
+                .map_err(|_| Error::parse(format!("invalid day {:?}", day)).at_line(line_idx))?;
+            let part = match part {
+                "1" => 1,
+                "2" => 2,
+                _ => {
+                    let message = format!("invalid part {:?}", part);
+                    return Err(Error::parse(message).at_line(line_idx));
+                }
+            };
+            data.insert((day, part), answer.trim().to_string());
+        }
+        Ok(Self { data })
+    }
+    /// Where the answers file lives, `$ADVENT_ANSWERS_FILE` or `answers.txt` in the input directory
+    pub fn path(loader: &InputLoader) -> PathBuf {
+        match std::env::var_os(ANSWERS_FILE_VAR) {
+            Some(path) if !path.is_empty() => path.into(),
+            _ => loader.dir().join(ANSWERS_FILE),
+        }
+    }
+    pub fn get(&self, day: u8, part: u8) -> Option<&str> {
+        self.data.get(&(day, part)).map(String::as_str)
+    }
+    pub fn insert(&mut self, day: u8, part: u8, answer: impl Display) {
+        self.data.insert((day, part), answer.to_string());
+    }
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    pub fn check(&self, day: u8, part: u8, actual: impl Display) -> Verdict {
+        match self.get(day, part) {
+            None => Verdict::Missing,
+            Some(expected) if expected == actual.to_string() => Verdict::Pass,
+            Some(expected) => Verdict::Fail {
+                expected: expected.to_string(),
+            },
+        }
+    }
+}
+
+impl Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# <day> <part> <answer>")?;
+        for ((day, part), answer) in self.data.iter() {
+            writeln!(f, "{} {} {}", day, part, answer)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "pass"),
+            Verdict::Fail { expected } => write!(f, "FAIL, expected {}", expected),
+            Verdict::Missing => write!(f, "missing"),
+        }
+    }
+}
+
+/// Assert that a day's personal answers match any that have been recorded
+#[cfg(test)]
+pub(crate) fn assert_recorded(day: u8, (part1, part2): (impl Display, impl Display)) {
+    let path = Answers::path(&InputLoader::from_env());
+    let Ok(text) = std::fs::read_to_string(&path) else {
+        eprintln!("day {}: {} / {}, no answers to check", day, part1, part2);
+        return;
+    };
+    let answers = Answers::parse(&text).unwrap();
+    for (part, actual) in [(1, part1.to_string()), (2, part2.to_string())] {
+        match answers.check(day, part, &actual) {
+            Verdict::Fail { expected } => {
+                panic!(
+                    "day {} part {}: expected {}, got {}",
+                    day, part, expected, actual
+                )
+            }
+            Verdict::Missing => eprintln!("day {} part {}: {}, not recorded", day, part, actual),
+            Verdict::Pass => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Answers, Verdict};
+
+    #[test]
+    fn round_trip() {
+        let text = "# mine\n1 1 11\n1 2 31\n\n17 1 4,6,3,5\n23 2 co,de,ka,ta\n";
+        let answers = Answers::parse(text).unwrap();
+        assert_eq!(answers.len(), 4);
+        assert_eq!(answers.get(17, 1), Some("4,6,3,5"));
+        assert_eq!(Answers::parse(&answers.to_string()).unwrap(), answers);
+    }
+
+    #[test]
+    fn verdicts() {
+        let mut answers = Answers::default();
+        answers.insert(1, 1, 11);
+        assert_eq!(answers.check(1, 1, 11), Verdict::Pass);
+        let expected = "11".to_string();
+        assert_eq!(answers.check(1, 1, 12), Verdict::Fail { expected });
+        assert_eq!(answers.check(1, 2, 31), Verdict::Missing);
+    }
+
+    #[test]
+    fn bad_line() {
+        let err = Answers::parse("1 1 11\n1 3 5\n").unwrap_err();
+        assert_eq!(err.line(), Some(2));
+        assert!(Answers::parse("1 1\n").is_err());
+    }
+}
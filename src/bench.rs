@@ -0,0 +1,329 @@
+use std::fmt::Write;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crate::error::parse_token;
+use crate::{Error, Result};
+
+/// Header of the CSV summary, which is also the format of a stored baseline
+const CSV_HEADER: &str = "day,stage,runs,min_ns,median_ns,mean_ns,stddev_ns";
+
+/// Summary of repeated timings of one stage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let runs = sorted.len();
+        if runs == 0 {
+            return Self {
+                runs,
+                min: Duration::ZERO,
+                median: Duration::ZERO,
+                mean: Duration::ZERO,
+                stddev: Duration::ZERO,
+            };
+        }
+        let median = if runs.is_multiple_of(2) {
+            (sorted[runs / 2 - 1] + sorted[runs / 2]) / 2
+        } else {
+            sorted[runs / 2]
+        };
+        let mean = sorted.iter().map(Duration::as_secs_f64).sum::<f64>() / runs as f64;
+        let variance = sorted
+            .iter()
+            .map(|sample| {
+                let diff = sample.as_secs_f64() - mean;
+                diff * diff
+            })
+            .sum::<f64>()
+            / runs as f64;
+        Self {
+            runs,
+            min: sorted[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        }
+    }
+}
+
+/// Timings of each stage of a day, measured separately
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Timings {
+    pub parse: Stats,
+    pub part1: Stats,
+    pub part2: Stats,
+}
+
+impl Timings {
+    pub fn stages(&self) -> [(&'static str, &Stats); 3] {
+        [
+            ("parse", &self.parse),
+            ("part1", &self.part1),
+            ("part2", &self.part2),
+        ]
+    }
+}
+
+/// Time `function` on a fresh value from `setup` each run, leaving the setup untimed
+pub fn measure<T, O>(
+    runs: usize,
+    mut setup: impl FnMut() -> T,
+    mut function: impl FnMut(T) -> O,
+) -> Stats {
+    let samples: Vec<_> = (0..runs)
+        .map(|_| {
+            let data = setup();
+            let start = Instant::now();
+            black_box(function(black_box(data)));
+            start.elapsed()
+        })
+        .collect();
+    Stats::from_samples(&samples)
+}
+
+/// Time parsing, then each part on its own copy of the parsed input
+pub fn stages<'a, T: Clone, A, B>(
+    input: &'a str,
+    runs: usize,
+    parse: impl Fn(&'a str) -> Result<T>,
+    part1: impl Fn(T) -> A,
+    part2: impl Fn(T) -> B,
+) -> Result<Timings> {
+    let parsed = parse(input)?;
+    Ok(Timings {
+        parse: measure(runs, || input, &parse),
+        part1: measure(runs, || parsed.clone(), part1),
+        part2: measure(runs, || parsed.clone(), part2),
+    })
+}
+
+/// One row per day and stage, with durations in nanoseconds
+pub fn to_csv(results: &[(u8, Timings)]) -> String {
+    let mut out = format!("{}\n", CSV_HEADER);
+    for (day, timings) in results {
+        for (stage, stats) in timings.stages() {
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{}",
+                day,
+                stage,
+                stats.runs,
+                stats.min.as_nanos(),
+                stats.median.as_nanos(),
+                stats.mean.as_nanos(),
+                stats.stddev.as_nanos()
+            )
+            .unwrap();
+        }
+    }
+    out
+}
+
+/// Read back a summary written by [`to_csv`], e.g. a stored baseline
+pub fn from_csv(text: &str) -> Result<Vec<(u8, Timings)>> {
+    let mut lines = text.lines().enumerate();
+    match lines.next() {
+        Some((_, header)) if header.trim() == CSV_HEADER => {}
+        _ => return Err(Error::parse(format!("expected the header {}", CSV_HEADER)).at_line(0)),
+    }
+    let mut results: Vec<(u8, [Option<Stats>; 3])> = Vec::new();
+    for (line_idx, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<_> = line.split(',').map(str::trim).collect();
+        let [day, stage, runs, min, median, mean, stddev] = fields[..] else {
+            return Err(Error::parse("expected 7 fields").at_line(line_idx));
+        };
+        let nanos = |field| parse_token(line_idx, line, field).map(Duration::from_nanos);
+        let stats = Stats {
+            runs: parse_token(line_idx, line, runs)?,
+            min: nanos(min)?,
+            median: nanos(median)?,
+            mean: nanos(mean)?,
+            stddev: nanos(stddev)?,
+        };
+        let stage_idx = match stage {
+            "parse" => 0,
+            "part1" => 1,
+            "part2" => 2,
+            _ => {
+                let message = format!("unknown stage {:?}", stage);
+                return Err(Error::parse(message).at_line(line_idx));
+            }
+        };
+        let day: u8 = parse_token(line_idx, line, day)?;
+        match results.last_mut() {
+            Some((last, stages)) if *last == day => stages[stage_idx] = Some(stats),
+            _ => {
+                let mut stages = [None; 3];
+                stages[stage_idx] = Some(stats);
+                results.push((day, stages));
+            }
+        }
+    }
+    results
+        .into_iter()
+        .map(|(day, stages)| match stages {
+            [Some(parse), Some(part1), Some(part2)] => Ok((
+                day,
+                Timings {
+                    parse,
+                    part1,
+                    part2,
+                },
+            )),
+            _ => Err(Error::parse(format!("day {} is missing a stage", day))),
+        })
+        .collect()
+}
+
+/// A stage whose median time grew by more than the allowed percentage
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub day: u8,
+    pub stage: &'static str,
+    pub baseline: Duration,
+    pub current: Duration,
+}
+
+impl Regression {
+    /// How much slower the stage got, as a percentage of the baseline
+    pub fn percent(&self) -> f64 {
+        percent_change(self.baseline, self.current)
+    }
+}
+
+fn percent_change(baseline: Duration, current: Duration) -> f64 {
+    let baseline = baseline.as_secs_f64();
+    if baseline == 0.0 {
+        return 0.0;
+    }
+    (current.as_secs_f64() - baseline) / baseline * 100.0
+}
+
+/// Compare median times against a baseline, ignoring days the baseline does not cover
+pub fn regressions(
+    baseline: &[(u8, Timings)],
+    current: &[(u8, Timings)],
+    threshold_percent: f64,
+) -> Vec<Regression> {
+    let mut out = Vec::new();
+    for (day, timings) in current {
+        let Some((_, before)) = baseline.iter().find(|(other, _)| other == day) else {
+            continue;
+        };
+        for ((stage, before), (_, after)) in before.stages().into_iter().zip(timings.stages()) {
+            if percent_change(before.median, after.median) > threshold_percent {
+                out.push(Regression {
+                    day: *day,
+                    stage,
+                    baseline: before.median,
+                    current: after.median,
+                });
+            }
+        }
+    }
+    out
+}
+
+/// An array of days, each with its stages, with durations in nanoseconds
+pub fn to_json(results: &[(u8, Timings)]) -> String {
+    let days: Vec<_> = results
+        .iter()
+        .map(|(day, timings)| {
+            let stages: Vec<_> = timings
+                .stages()
+                .iter()
+                .map(|(stage, stats)| {
+                    format!(
+                        "\"{}\": {{\"runs\": {}, \"min_ns\": {}, \"median_ns\": {}, \"mean_ns\": {}, \"stddev_ns\": {}}}",
+                        stage,
+                        stats.runs,
+                        stats.min.as_nanos(),
+                        stats.median.as_nanos(),
+                        stats.mean.as_nanos(),
+                        stats.stddev.as_nanos()
+                    )
+                })
+                .collect();
+            format!("  {{\"day\": {}, {}}}", day, stages.join(", "))
+        })
+        .collect();
+    format!("[\n{}\n]\n", days.join(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_csv, regressions, to_csv, Stats, Timings};
+    use std::time::Duration;
+
+    #[test]
+    fn summary_statistics() {
+        let samples = [4, 1, 3, 2].map(Duration::from_millis);
+        let stats = Stats::from_samples(&samples);
+        assert_eq!(stats.runs, 4);
+        assert_eq!(stats.min, Duration::from_millis(1));
+        assert_eq!(stats.median, Duration::from_micros(2500));
+        assert_eq!(stats.mean, Duration::from_micros(2500));
+        // Population standard deviation of 1, 2, 3, 4 is sqrt(1.25)
+        let stddev = stats.stddev.as_secs_f64() * 1000.0;
+        assert!((stddev - 1.25f64.sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn csv_rows() {
+        let stats = Stats::from_samples(&[Duration::from_nanos(10)]);
+        let timings = Timings {
+            parse: stats,
+            part1: stats,
+            part2: stats,
+        };
+        let csv = to_csv(&[(3, timings)]);
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[2], "3,part1,1,10,10,10,0");
+        assert_eq!(from_csv(&csv).unwrap(), [(3, timings)]);
+    }
+
+    #[test]
+    fn bad_baseline() {
+        assert!(from_csv("3,parse,1,10,10,10,0\n").is_err());
+        let header = "day,stage,runs,min_ns,median_ns,mean_ns,stddev_ns\n";
+        let err = from_csv(&format!("{}3,parse,1,10,x,10,0\n", header)).unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(2), Some(14)));
+        let missing = format!("{}3,parse,1,10,10,10,0\n", header);
+        assert!(from_csv(&missing).is_err());
+    }
+
+    #[test]
+    fn flag_regressions() {
+        let timings = |nanos| {
+            let stats = Stats::from_samples(&[Duration::from_nanos(nanos)]);
+            Timings {
+                parse: stats,
+                part1: stats,
+                part2: stats,
+            }
+        };
+        let mut slower = timings(100);
+        slower.part2 = Stats::from_samples(&[Duration::from_nanos(130)]);
+        let baseline = [(1, timings(100)), (2, timings(100))];
+        let current = [(1, slower), (2, timings(105)), (3, timings(1000))];
+        let found = regressions(&baseline, &current, 10.0);
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].day, found[0].stage), (1, "part2"));
+        assert!((found[0].percent() - 30.0).abs() < 1e-9);
+        assert_eq!(regressions(&baseline, &current, 50.0), []);
+    }
+}
@@ -0,0 +1,147 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The input does not match the puzzle format
+    Parse(String),
+    /// The input is well formed, but the puzzle has no answer for it
+    NoSolution(String),
+    /// The solver panicked, which is a bug rather than a problem with the input
+    Panic(String),
+}
+
+/// Error produced while solving a day, pointing at the offending input where possible
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    day: Option<u8>,
+    line: Option<usize>,
+    column: Option<usize>,
+    kind: ErrorKind,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    fn new(kind: ErrorKind) -> Self {
+        Self {
+            day: None,
+            line: None,
+            column: None,
+            kind,
+        }
+    }
+    pub fn parse(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Parse(message.into()))
+    }
+    pub fn no_solution(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::NoSolution(message.into()))
+    }
+    pub fn panic(message: impl Into<String>) -> Self {
+        Self::new(ErrorKind::Panic(message.into()))
+    }
+    pub fn unexpected_char(c: char) -> Self {
+        Self::parse(format!("unexpected character {:?}", c))
+    }
+    /// Point the error at a line, given as a 0-based index like `lines().enumerate()` produces
+    pub fn at_line(mut self, line_idx: usize) -> Self {
+        self.line = Some(line_idx + 1);
+        self
+    }
+    /// Point the error at a 0-based line and column index
+    pub fn at(mut self, line_idx: usize, col_idx: usize) -> Self {
+        self.column = Some(col_idx + 1);
+        self.at_line(line_idx)
+    }
+    /// Point the error at a 0-based line and column index, unless it already has a position
+    pub(crate) fn or_at(self, line_idx: usize, col_idx: usize) -> Self {
+        match self.line {
+            Some(_) => self,
+            None => self.at(line_idx, col_idx),
+        }
+    }
+    /// Record which day failed, unless it is already known
+    pub fn with_day(mut self, day: u8) -> Self {
+        self.day.get_or_insert(day);
+        self
+    }
+    pub fn day(&self) -> Option<u8> {
+        self.day
+    }
+    /// 1-based line number of the offending input
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+    /// 1-based column number of the offending input
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {}: ", day)?;
+        }
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "line {}, column {}: ", line, column)?,
+            (Some(line), None) => write!(f, "line {}: ", line)?,
+            _ => {}
+        }
+        match &self.kind {
+            ErrorKind::Parse(message) => write!(f, "invalid input, {}", message),
+            ErrorKind::NoSolution(message) => write!(f, "no solution, {}", message),
+            ErrorKind::Panic(message) => write!(f, "panicked, {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// 0-based column, in characters like [`crate::Grid::parse`] counts them, where `part`, a slice
+/// of `line`, starts
+pub(crate) fn column(line: &str, part: &str) -> usize {
+    let offset = (part.as_ptr() as usize).saturating_sub(line.as_ptr() as usize);
+    line.get(..offset)
+        .map_or(0, |before| before.chars().count())
+}
+
+/// Parse `token`, a slice of `line`, pointing any error at where the token starts
+pub(crate) fn parse_token<T: FromStr>(line_idx: usize, line: &str, token: &str) -> Result<T> {
+    token.parse().map_err(|_| {
+        let message = format!("expected a number, found {:?}", token);
+        Error::parse(message).at(line_idx, column(line, token))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_token, Error};
+
+    #[test]
+    fn display_position() {
+        let err = Error::unexpected_char('x').at(2, 4).with_day(6);
+        assert_eq!(
+            err.to_string(),
+            "day 6: line 3, column 5: invalid input, unexpected character 'x'"
+        );
+        let err = Error::no_solution("no path to the exit");
+        assert_eq!(err.to_string(), "no solution, no path to the exit");
+    }
+
+    #[test]
+    fn token_column() {
+        let line = "3   x4";
+        let token = line.split_whitespace().nth(1).unwrap();
+        let err = parse_token::<i32>(0, line, token).unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(1), Some(5)));
+        // Columns count characters, not bytes
+        let line = "é → x4";
+        let token = line.split_whitespace().nth(2).unwrap();
+        let err = parse_token::<i32>(0, line, token).unwrap_err();
+        assert_eq!(err.column(), Some(5));
+    }
+}
@@ -0,0 +1,51 @@
+//! Malformed inputs, to check that every day rejects them with an error rather than a panic
+
+use std::collections::BTreeSet;
+
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+/// One change to an example
+#[derive(Debug, Clone, Copy)]
+enum Edit {
+    Delete,
+    Insert,
+    Replace,
+    Truncate,
+}
+
+/// The example with a few characters deleted, inserted or replaced, or cut short, and now and
+/// then any text at all.
+///
+/// Edits use characters the example already has, digits, separators and a character of more
+/// than one byte, so most of them get past the first checks of the parser
+pub fn mutations(example: &'static str) -> impl Strategy<Value = String> {
+    let alphabet: BTreeSet<char> = example.chars().chain("0123456789-,: \né".chars()).collect();
+    let edit = (
+        prop_oneof![
+            Just(Edit::Delete),
+            Just(Edit::Insert),
+            Just(Edit::Replace),
+            Just(Edit::Truncate),
+        ],
+        any::<Index>(),
+        prop::sample::select(Vec::from_iter(alphabet)),
+    );
+    let edited = prop::collection::vec(edit, 1..5).prop_map(move |edits| {
+        let mut chars: Vec<char> = example.chars().collect();
+        for (edit, at, symbol) in edits {
+            let at = at.index(chars.len() + 1);
+            match edit {
+                Edit::Insert => chars.insert(at, symbol),
+                Edit::Truncate => chars.truncate(at),
+                _ if at == chars.len() => {}
+                Edit::Delete => {
+                    chars.remove(at);
+                }
+                Edit::Replace => chars[at] = symbol,
+            }
+        }
+        chars.into_iter().collect()
+    });
+    prop_oneof![4 => edited, 1 => any::<String>()]
+}
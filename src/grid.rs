@@ -0,0 +1,226 @@
+use std::fmt::{self, Display};
+use std::ops::{Index, IndexMut};
+
+use crate::{Direction, Error, Result};
+
+/// Rectangular grid stored row by row, addressed by `(row, col)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    data: Vec<T>,
+    num_rows: usize,
+    num_cols: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(data: Vec<T>, num_rows: usize, num_cols: usize) -> Self {
+        assert_eq!(data.len(), num_rows * num_cols, "grid data does not fit");
+        Self {
+            data,
+            num_rows,
+            num_cols,
+        }
+    }
+    pub fn filled(num_rows: usize, num_cols: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::new(vec![value; num_rows * num_cols], num_rows, num_cols)
+    }
+    /// Parse a character map, one row per line, converting each character with `cell`.
+    ///
+    /// Errors from `cell` point at its character unless they already carry a position
+    pub fn parse(input: &str, cell: impl FnMut((usize, usize), char) -> Result<T>) -> Result<Self> {
+        Self::parse_lines(input.lines().enumerate(), cell)
+    }
+    /// Like [`Grid::parse`], but from numbered lines, for maps that are only part of the input
+    pub fn parse_lines<'a>(
+        lines: impl IntoIterator<Item = (usize, &'a str)>,
+        mut cell: impl FnMut((usize, usize), char) -> Result<T>,
+    ) -> Result<Self> {
+        let mut data = Vec::new();
+        let mut num_rows = 0;
+        let mut num_cols = 0;
+        for (row, (line_idx, line)) in lines.into_iter().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                data.push(cell((row, col), c).map_err(|err| err.or_at(line_idx, col))?);
+            }
+            let width = data.len() - num_rows * num_cols;
+            if row == 0 {
+                num_cols = width;
+            } else if width != num_cols {
+                let message = format!("expected a row of width {}, found {}", num_cols, width);
+                return Err(Error::parse(message).at_line(line_idx));
+            }
+            num_rows += 1;
+        }
+        Ok(Self::new(data, num_rows, num_cols))
+    }
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+    /// Number of cells
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    pub fn contains(&self, (row, col): (usize, usize)) -> bool {
+        row < self.num_rows && col < self.num_cols
+    }
+    /// Whether the position is in the outermost ring of cells
+    pub fn on_edge(&self, (row, col): (usize, usize)) -> bool {
+        row == 0 || col == 0 || row + 1 == self.num_rows || col + 1 == self.num_cols
+    }
+    /// Index of a position in the row-major cell order, for side tables like distances
+    pub fn index_of(&self, (row, col): (usize, usize)) -> usize {
+        row * self.num_cols + col
+    }
+    /// Position of a row-major index, the inverse of [`Grid::index_of`]
+    pub fn position(&self, idx: usize) -> (usize, usize) {
+        (idx / self.num_cols, idx % self.num_cols)
+    }
+    pub fn get(&self, position: (usize, usize)) -> Option<&T> {
+        if self.contains(position) {
+            Some(&self.data[self.index_of(position)])
+        } else {
+            None
+        }
+    }
+    pub fn get_mut(&mut self, position: (usize, usize)) -> Option<&mut T> {
+        if self.contains(position) {
+            let idx = self.index_of(position);
+            Some(&mut self.data[idx])
+        } else {
+            None
+        }
+    }
+    /// The neighboring position in a direction, if it is inside the grid
+    pub fn step(&self, position: (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+        direction.step(position, (self.num_rows, self.num_cols))
+    }
+    /// Orthogonal neighbors inside the grid, clockwise from north
+    pub fn neighbors(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        Direction::CARDINAL
+            .into_iter()
+            .filter_map(move |direction| self.step(position, direction))
+    }
+    pub fn row(&self, row: usize) -> &[T] {
+        &self.data[row * self.num_cols..(row + 1) * self.num_cols]
+    }
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // `chunks` panics on a zero size, which only an empty grid would have
+        self.data.chunks(self.num_cols.max(1))
+    }
+    pub fn column(&self, col: usize) -> impl Iterator<Item = &T> {
+        assert!(col < self.num_cols, "column {} out of bounds", col);
+        self.data.iter().skip(col).step_by(self.num_cols)
+    }
+    /// Every position, in row-major order
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let num_cols = self.num_cols;
+        (0..self.num_rows).flat_map(move |row| (0..num_cols).map(move |col| (row, col)))
+    }
+    /// Every cell with its position, in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.data.iter())
+    }
+    /// Position of the first cell equal to `value`, in row-major order
+    pub fn find(&self, value: &T) -> Option<(usize, usize)>
+    where
+        T: PartialEq,
+    {
+        let idx = self.data.iter().position(|cell| cell == value)?;
+        Some(self.position(idx))
+    }
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::new(
+            self.data.iter().map(f).collect(),
+            self.num_rows,
+            self.num_cols,
+        )
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: (usize, usize)) -> &T {
+        assert!(self.contains(position), "{:?} out of bounds", position);
+        &self.data[self.index_of(position)]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, position: (usize, usize)) -> &mut T {
+        assert!(self.contains(position), "{:?} out of bounds", position);
+        let idx = self.index_of(position);
+        &mut self.data[idx]
+    }
+}
+
+/// Renders one line per row, so a grid of `char` displays as the map it was parsed from
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Grid;
+    use crate::{Direction, Error};
+
+    const MAP: &str = "\
+#..
+.#E
+";
+
+    fn parse(input: &str) -> crate::Result<Grid<char>> {
+        Grid::parse(input, |_, c| Ok(c))
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let grid = parse(MAP).unwrap();
+        assert_eq!((grid.num_rows(), grid.num_cols()), (2, 3));
+        assert_eq!(grid[(1, 2)], 'E');
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.find(&'E'), Some((1, 2)));
+        assert_eq!(grid.row(1), ['.', '#', 'E']);
+        assert_eq!(grid.column(1).collect::<String>(), ".#");
+        assert_eq!(grid.to_string(), MAP);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = parse("..\n...\n").unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(2), None));
+        let wall = |_, c| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(Error::unexpected_char(c)),
+        };
+        let err = Grid::parse(MAP, wall).unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(2), Some(3)));
+    }
+
+    #[test]
+    fn neighbors_in_bounds() {
+        let grid = parse(MAP).unwrap();
+        let corner: Vec<_> = grid.neighbors((0, 0)).collect();
+        assert_eq!(corner, [(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbors((1, 1)).count(), 3);
+        assert_eq!(grid.step((1, 2), Direction::East), None);
+        assert_eq!(grid.step((1, 2), Direction::North), Some((0, 2)));
+    }
+}
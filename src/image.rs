@@ -0,0 +1,184 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::{Grid, Result};
+
+/// Draws a picture of a day's input and answer
+pub type Picture = fn(&str) -> Result<Image>;
+
+/// 8-bit red, green and blue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const GRAY: Rgb = Rgb(64, 64, 64);
+    pub const RED: Rgb = Rgb(220, 40, 40);
+    pub const GREEN: Rgb = Rgb(40, 180, 60);
+
+    /// Color on a scale from blue for 0 through green to red for 1
+    pub fn heat(fraction: f64) -> Self {
+        let fraction = fraction.clamp(0.0, 1.0);
+        let channel = |x: f64| (255.0 * x.clamp(0.0, 1.0)).round() as u8;
+        Rgb(
+            channel(2.0 * fraction - 1.0),
+            channel(1.0 - (2.0 * fraction - 1.0).abs()),
+            channel(1.0 - 2.0 * fraction),
+        )
+    }
+    /// A bright color for each label, with neighboring labels far apart in hue
+    pub fn label(label: usize) -> Self {
+        // Stepping by the golden ratio spreads any number of hues evenly around the wheel
+        let hue = (label as f64 * 0.618_034).fract() * 6.0;
+        let rising = hue.fract();
+        let (red, green, blue) = match hue as u8 {
+            0 => (1.0, rising, 0.0),
+            1 => (1.0 - rising, 1.0, 0.0),
+            2 => (0.0, 1.0, rising),
+            3 => (0.0, 1.0 - rising, 1.0),
+            4 => (rising, 0.0, 1.0),
+            _ => (1.0, 0.0, 1.0 - rising),
+        };
+        let channel = |x: f64| (55.0 + 200.0 * x).round() as u8;
+        Rgb(channel(red), channel(green), channel(blue))
+    }
+}
+
+/// A picture of a grid, one square of `scale` by `scale` pixels per cell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pixels: Grid<Rgb>,
+    scale: usize,
+}
+
+impl Image {
+    /// Color every cell of a grid with `color`
+    pub fn new<T>(grid: &Grid<T>, color: impl FnMut(&T) -> Rgb) -> Self {
+        Self {
+            pixels: grid.map(color),
+            scale: 1,
+        }
+    }
+    pub fn filled(num_rows: usize, num_cols: usize, color: Rgb) -> Self {
+        Self {
+            pixels: Grid::filled(num_rows, num_cols, color),
+            scale: 1,
+        }
+    }
+    /// Color over one cell, ignoring positions outside the image
+    pub fn set(&mut self, position: (usize, usize), color: Rgb) {
+        if let Some(old) = self.pixels.get_mut(position) {
+            *old = color;
+        }
+    }
+    pub fn scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+    pub fn width(&self) -> usize {
+        self.pixels.num_cols() * self.scale
+    }
+    pub fn height(&self) -> usize {
+        self.pixels.num_rows() * self.scale
+    }
+    /// Red, green and blue bytes of every pixel, row by row
+    fn bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(3 * self.width() * self.height());
+        for row in self.pixels.rows() {
+            let start = out.len();
+            for &Rgb(red, green, blue) in row {
+                for _ in 0..self.scale {
+                    out.extend([red, green, blue]);
+                }
+            }
+            let end = out.len();
+            for _ in 1..self.scale {
+                out.extend_from_within(start..end);
+            }
+        }
+        out
+    }
+    /// Write the image as a binary PPM, which most image viewers open
+    pub fn write_ppm(&self, mut out: impl Write) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width(), self.height())?;
+        out.write_all(&self.bytes())
+    }
+    #[cfg(feature = "png")]
+    pub fn write_png(&self, out: impl Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(out, self.width() as u32, self.height() as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(&self.bytes())
+            .map_err(io::Error::other)
+    }
+    /// Write the image to a `.ppm` file, or a `.png` file with the `png` feature
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        match extension {
+            Some("ppm") => {
+                let mut out = BufWriter::new(File::create(path)?);
+                self.write_ppm(&mut out)?;
+                out.flush()
+            }
+            #[cfg(feature = "png")]
+            Some("png") => {
+                let mut out = BufWriter::new(File::create(path)?);
+                self.write_png(&mut out)?;
+                out.flush()
+            }
+            #[cfg(not(feature = "png"))]
+            Some("png") => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "PNG images need a build with the png feature",
+            )),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "images are saved as .ppm or .png files",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Image, Rgb};
+    use crate::Grid;
+
+    #[test]
+    fn ppm() {
+        let grid = Grid::new(vec![true, false], 1, 2);
+        let mut image = Image::new(&grid, |&wall| if wall { Rgb::BLACK } else { Rgb::WHITE });
+        image.set((0, 1), Rgb(1, 2, 3));
+        image.set((3, 3), Rgb::RED);
+        let mut out = Vec::new();
+        image.scale(3).write_ppm(&mut out).unwrap();
+        let header = b"P6\n6 3\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        let row = [0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 1, 2, 3, 1, 2, 3];
+        assert_eq!(&out[header.len()..], [row, row, row].concat());
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(Rgb::heat(0.0), Rgb(0, 0, 255));
+        assert_eq!(Rgb::heat(0.5), Rgb(0, 255, 0));
+        assert_eq!(Rgb::heat(2.0), Rgb(255, 0, 0));
+        let labels: Vec<_> = (0..20).map(Rgb::label).collect();
+        for (idx, color) in labels.iter().enumerate() {
+            assert!(!labels[idx + 1..].contains(color));
+        }
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn png() {
+        let mut out = Vec::new();
+        Image::filled(2, 3, Rgb::GREEN).write_png(&mut out).unwrap();
+        assert_eq!(&out[..8], b"\x89PNG\r\n\x1a\n");
+    }
+}
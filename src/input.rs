@@ -0,0 +1,217 @@
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "fetch")]
+use crate::site::Client;
+
+/// Environment variable that overrides the input directory
+pub const INPUT_DIR_VAR: &str = "ADVENT_INPUT_DIR";
+/// Directory searched when nothing else is configured, relative to the working directory
+pub const DEFAULT_INPUT_DIR: &str = "input";
+/// File name of each day's input, with `{day}` replaced by the day number
+pub const DEFAULT_NAMING: &str = "p{day}.txt";
+
+/// Reads personal puzzle inputs from disk at runtime, downloading missing ones if it has a client
+#[derive(Debug, Clone)]
+pub struct InputLoader {
+    dir: PathBuf,
+    naming: String,
+    #[cfg(feature = "fetch")]
+    client: Option<Client>,
+}
+
+impl InputLoader {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            naming: DEFAULT_NAMING.to_string(),
+            #[cfg(feature = "fetch")]
+            client: None,
+        }
+    }
+    /// Use the directory in `ADVENT_INPUT_DIR` if it is set, otherwise `input`
+    pub fn from_env() -> Self {
+        match std::env::var_os(INPUT_DIR_VAR) {
+            Some(dir) if !dir.is_empty() => Self::new(dir),
+            _ => Self::new(DEFAULT_INPUT_DIR),
+        }
+    }
+    /// Change the file naming scheme, e.g. `day{day}.txt`
+    pub fn with_naming(mut self, naming: impl Into<String>) -> Self {
+        self.naming = naming.into();
+        self
+    }
+    /// Download inputs that are not on disk yet, saving them where they would have been read
+    #[cfg(feature = "fetch")]
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    pub fn path(&self, day: u8) -> PathBuf {
+        self.dir
+            .join(self.naming.replace("{day}", &day.to_string()))
+    }
+    pub fn exists(&self, day: u8) -> bool {
+        self.path(day).is_file()
+    }
+    /// Read a day's input. A missing input is downloaded and cached if there is a client,
+    /// so each day is fetched at most once
+    pub fn load(&self, day: u8) -> std::io::Result<String> {
+        let path = self.path(day);
+        #[cfg(feature = "fetch")]
+        if let Some(client) = self.client.as_ref().filter(|_| !path.is_file()) {
+            let input = client.input(day)?;
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(&path, &input)?;
+            return Ok(input);
+        }
+        std::fs::read_to_string(path)
+    }
+}
+
+impl Default for InputLoader {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+/// Every file `pattern` names: the files in it if it is a directory, otherwise the files whose
+/// name matches its last component, where `*` stands for any run of characters and `?` for one
+pub fn expand(pattern: impl AsRef<Path>) -> std::io::Result<Vec<PathBuf>> {
+    let pattern = pattern.as_ref();
+    let (dir, name_pattern) = if pattern.is_dir() {
+        (pattern, "*".to_string())
+    } else {
+        let dir = match pattern.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let name = pattern.file_name().unwrap_or_default();
+        (dir, name.to_string_lossy().into_owned())
+    };
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_file() && wildcard_match(&name_pattern, &name) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // matched[j] is whether the pattern so far matches the first j characters of the name
+    let mut matched = vec![false; name.len() + 1];
+    matched[0] = true;
+    for p in pattern {
+        let mut next = vec![false; name.len() + 1];
+        for j in 0..=name.len() {
+            next[j] = match p {
+                '*' => matched[j] || (j > 0 && next[j - 1]),
+                '?' => j > 0 && matched[j - 1],
+                c => j > 0 && matched[j - 1] && name[j - 1] == c,
+            };
+        }
+        matched = next;
+    }
+    matched[name.len()]
+}
+
+/// Load a day's personal input for a test, or explain why the test is being skipped
+#[cfg(test)]
+pub(crate) fn personal(day: u8) -> Option<String> {
+    let loader = InputLoader::from_env();
+    match loader.load(day) {
+        Ok(input) => Some(input),
+        Err(err) => {
+            let path = loader.path(day);
+            eprintln!(
+                "skipping day {}, cannot read {}: {}",
+                day,
+                path.display(),
+                err
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand, wildcard_match, InputLoader};
+    use std::path::Path;
+
+    #[test]
+    fn naming() {
+        let loader = InputLoader::new("inputs");
+        assert_eq!(loader.path(7), Path::new("inputs").join("p7.txt"));
+        let loader = loader.with_naming("day{day}/input");
+        assert_eq!(loader.path(12), Path::new("inputs").join("day12/input"));
+    }
+
+    #[test]
+    fn missing_file() {
+        let loader = InputLoader::new("no/such/directory");
+        assert!(!loader.exists(1));
+        assert!(loader.load(1).is_err());
+    }
+
+    #[cfg(feature = "fetch")]
+    #[test]
+    fn fetch_once() {
+        use crate::site::{stand_in, Client};
+
+        let dir = std::env::temp_dir().join(format!("advent2024-fetch-{}", std::process::id()));
+        let (base_url, server) = stand_in::serve(vec![(200, "3   4\n")]);
+        let loader = InputLoader::new(&dir).with_client(Client::new(base_url, "secret"));
+        assert!(!loader.exists(1));
+        assert_eq!(loader.load(1).unwrap(), "3   4\n");
+        // The stand-in only answers once, so this must come from the cache
+        assert_eq!(loader.load(1).unwrap(), "3   4\n");
+        assert_eq!(server.join().unwrap().len(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn wildcards() {
+        assert!(wildcard_match("p5-*.txt", "p5-alice.txt"));
+        assert!(wildcard_match("p5-*.txt", "p5-.txt"));
+        assert!(!wildcard_match("p5-*.txt", "p15-bob.txt"));
+        assert!(wildcard_match("p?.txt", "p7.txt"));
+        assert!(!wildcard_match("p?.txt", "p17.txt"));
+        assert!(wildcard_match("*", ""));
+    }
+
+    #[test]
+    fn expand_inputs() {
+        let dir = std::env::temp_dir().join(format!("advent2024-expand-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        for name in ["p5-b.txt", "p5-a.txt", "p6-a.txt"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+        let names = |paths: Vec<std::path::PathBuf>| -> Vec<String> {
+            let names = paths.iter().map(|path| path.file_name().unwrap());
+            names
+                .map(|name| name.to_string_lossy().into_owned())
+                .collect()
+        };
+        assert_eq!(
+            names(expand(&dir).unwrap()),
+            ["p5-a.txt", "p5-b.txt", "p6-a.txt"]
+        );
+        assert_eq!(
+            names(expand(dir.join("p5-*")).unwrap()),
+            ["p5-a.txt", "p5-b.txt"]
+        );
+        assert!(expand(dir.join("missing").join("*")).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
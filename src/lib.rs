@@ -1,3 +1,11 @@
+pub mod answers;
+pub mod bench;
+mod error;
+#[cfg(test)]
+mod fuzz;
+pub mod grid;
+pub mod image;
+pub mod input;
 pub mod p1;
 pub mod p10;
 pub mod p11;
@@ -21,64 +29,289 @@ pub mod p6;
 pub mod p7;
 pub mod p8;
 pub mod p9;
+pub mod parallel;
+pub mod parse;
+pub mod point;
+pub mod report;
+pub mod search;
+#[cfg(feature = "fetch")]
+pub mod site;
+#[cfg(feature = "fetch")]
+pub mod submit;
+pub mod visual;
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Eq)]
+use std::fmt::Display;
+
+pub use answers::{Answers, Verdict};
+pub use bench::{Stats, Timings};
+pub use error::{Error, ErrorKind, Result};
+pub use grid::Grid;
+use image::Picture;
+pub use input::InputLoader;
+pub use point::{Point, Vec2};
+use visual::Animate;
+
+/// Type-erased answer to one part of a puzzle
+pub type Answer = Box<dyn Display + Send>;
+
+/// Common interface over every day, so runners and tests can treat them uniformly
+pub trait Solution: Sync {
+    fn day(&self) -> u8;
+    fn title(&self) -> &'static str;
+    fn part1(&self, input: &str) -> Result<Answer>;
+    fn part2(&self, input: &str) -> Result<Answer>;
+    /// Solve both parts at once. Days that share work between the parts override this
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        Ok((self.part1(input)?, self.part2(input)?))
+    }
+    /// Time parsing and each part separately, `runs` times each
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings>;
+    /// Play the day's simulation step by step, for days that have one
+    fn animate(&self) -> Option<Animate> {
+        None
+    }
+    /// Draw the day's grid, for days that have a picture
+    fn picture(&self) -> Option<Picture> {
+        None
+    }
+}
+
+/// Erase the answer type, tagging any error with the day that produced it
+fn answer<A>(day: u8, result: Result<A>) -> Result<Answer>
+where
+    A: Display + Send + 'static,
+{
+    match result {
+        Ok(part) => Ok(Box::new(part)),
+        Err(err) => Err(err.with_day(day)),
+    }
+}
+
+fn answers<A, B>(day: u8, result: Result<(A, B)>) -> Result<(Answer, Answer)>
+where
+    A: Display + Send + 'static,
+    B: Display + Send + 'static,
+{
+    match result {
+        Ok((part1, part2)) => Ok((Box::new(part1), Box::new(part2))),
+        Err(err) => Err(err.with_day(day)),
+    }
+}
+
+/// Every solved day, in order
+pub static DAYS: &[&dyn Solution] = &[
+    &p1::Day1,
+    &p2::Day2,
+    &p3::Day3,
+    &p4::Day4,
+    &p5::Day5,
+    &p6::Day6,
+    &p7::Day7,
+    &p8::Day8,
+    &p9::Day9,
+    &p10::Day10,
+    &p11::Day11,
+    &p12::Day12,
+    &p13::Day13,
+    &p14::Day14,
+    &p15::Day15,
+    &p16::Day16,
+    &p17::Day17,
+    &p18::Day18,
+    &p19::Day19,
+    &p20::Day20,
+    &p21::Day21,
+    &p22::Day22,
+    &p23::Day23,
+];
+
+/// Days that can show their simulation step by step
+pub fn animated() -> impl Iterator<Item = &'static dyn Solution> {
+    DAYS.iter()
+        .copied()
+        .filter(|solution| solution.animate().is_some())
+}
+
+/// Days that can draw a picture
+pub fn pictured() -> impl Iterator<Item = &'static dyn Solution> {
+    DAYS.iter()
+        .copied()
+        .filter(|solution| solution.picture().is_some())
+}
+
+/// Look up a day in the registry by its number
+pub fn get_day(day: u8) -> Option<&'static dyn Solution> {
+    DAYS.iter().copied().find(|solution| solution.day() == day)
+}
+
+/// Compass direction on a grid of `(row, col)` positions, where north is towards row 0.
+///
+/// The cardinal directions come first, so they can index arrays of four
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Eq, Hash)]
 pub enum Direction {
     North = 0,
     East,
     South,
     West,
+    NorthEast,
+    SouthEast,
+    SouthWest,
+    NorthWest,
 }
 
 impl Direction {
-    fn wide_orthongonal(origin: (usize, usize)) -> [(usize, usize); 4] {
-        [
-            Direction::North.get_square(Direction::North.get_square(origin)),
-            Direction::East.get_square(Direction::East.get_square(origin)),
-            Direction::South.get_square(Direction::South.get_square(origin)),
-            Direction::West.get_square(Direction::West.get_square(origin)),
-        ]
-    }
-    fn orthogonal(origin: (usize, usize)) -> [(usize, usize); 4] {
-        [
-            Direction::North.get_square(origin),
-            Direction::East.get_square(origin),
-            Direction::South.get_square(origin),
-            Direction::West.get_square(origin),
-        ]
-    }
-    fn get_square(self, origin: (usize, usize)) -> (usize, usize) {
-        let (row, col) = origin;
+    /// The four cardinal directions, clockwise from north
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+    /// The four diagonal directions, clockwise from north east
+    pub const DIAGONAL: [Direction; 4] = [
+        Direction::NorthEast,
+        Direction::SouthEast,
+        Direction::SouthWest,
+        Direction::NorthWest,
+    ];
+    /// All eight directions, cardinal first
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+        Direction::NorthEast,
+        Direction::SouthEast,
+        Direction::SouthWest,
+        Direction::NorthWest,
+    ];
+    /// Signed `(row, col)` change of one step
+    pub fn offset(self) -> (isize, isize) {
         match self {
-            Direction::North => (row.wrapping_sub(1), col),
-            Direction::East => (row, col + 1),
-            Direction::South => (row + 1, col),
-            Direction::West => (row, col.wrapping_sub(1)),
+            Direction::North => (-1, 0),
+            Direction::East => (0, 1),
+            Direction::South => (1, 0),
+            Direction::West => (0, -1),
+            Direction::NorthEast => (-1, 1),
+            Direction::SouthEast => (1, 1),
+            Direction::SouthWest => (1, -1),
+            Direction::NorthWest => (-1, -1),
         }
     }
-    fn turn(self) -> Self {
+    /// One step from `origin`, if it stays within `(num_rows, num_cols)`
+    pub fn step(self, origin: (usize, usize), bounds: (usize, usize)) -> Option<(usize, usize)> {
+        let (d_row, d_col) = self.offset();
+        let row = origin.0.checked_add_signed(d_row)?;
+        let col = origin.1.checked_add_signed(d_col)?;
+        (row < bounds.0 && col < bounds.1).then_some((row, col))
+    }
+    /// One step from `origin` with no bounds. Stepping off the top or left edge wraps around to
+    /// a huge index, which any later bounds check rejects, so prefer [`Direction::step`]
+    fn get_square(self, origin: (usize, usize)) -> (usize, usize) {
+        let (d_row, d_col) = self.offset();
+        (
+            origin.0.wrapping_add_signed(d_row),
+            origin.1.wrapping_add_signed(d_col),
+        )
+    }
+    /// Turn 90 degrees clockwise
+    pub fn turn(self) -> Self {
         match self {
             Direction::North => Direction::East,
             Direction::East => Direction::South,
             Direction::South => Direction::West,
             Direction::West => Direction::North,
+            Direction::NorthEast => Direction::SouthEast,
+            Direction::SouthEast => Direction::SouthWest,
+            Direction::SouthWest => Direction::NorthWest,
+            Direction::NorthWest => Direction::NorthEast,
         }
     }
+    /// Turn 90 degrees counterclockwise
+    pub fn turn_left(self) -> Self {
+        self.reverse().turn()
+    }
+    pub fn reverse(self) -> Self {
+        self.turn().turn()
+    }
     fn get_perp(self) -> (Self, Self) {
-        match self {
-            Direction::East | Direction::West => (Direction::North, Direction::South),
-            _ => (Direction::East, Direction::West),
+        (self.turn_left(), self.turn())
+    }
+    /// Parse a compass letter, `NESW`
+    pub fn from_letter(c: char) -> Result<Self> {
+        match c {
+            'N' => Ok(Direction::North),
+            'E' => Ok(Direction::East),
+            'S' => Ok(Direction::South),
+            'W' => Ok(Direction::West),
+            _ => Err(Error::unexpected_char(c)),
+        }
+    }
+}
+
+/// Parses the arrows `^>v<`, as drawn on the puzzle maps and move lists
+impl TryFrom<char> for Direction {
+    type Error = Error;
+
+    fn try_from(c: char) -> Result<Self> {
+        match c {
+            '^' => Ok(Direction::North),
+            '>' => Ok(Direction::East),
+            'v' => Ok(Direction::South),
+            '<' => Ok(Direction::West),
+            _ => Err(Error::unexpected_char(c)),
         }
     }
 }
 
+/// Quick timing of a whole solve, for use while working on a day
 pub fn simple_bench<O>(input: &str, function: fn(&str) -> O) {
     benchmarking::warm_up();
+    let stats = bench::measure(100, || input, function);
+    eprintln!(
+        "Median duration: {:?} (min {:?}, mean {:?}, stddev {:?})",
+        stats.median, stats.min, stats.mean, stats.stddev
+    );
+}
 
-    let bench_result = benchmarking::bench_function(|measurer| {
-        measurer.measure(|| function(input));
-    })
-    .unwrap();
-    assert!(bench_result.times() > 0);
-    eprintln!("Average duration: {:?}", bench_result.elapsed());
+#[cfg(test)]
+mod tests {
+    use super::Direction;
+
+    #[test]
+    fn registry_in_order() {
+        for (idx, solution) in super::DAYS.iter().enumerate() {
+            assert_eq!(solution.day() as usize, idx + 1);
+            assert!(!solution.title().is_empty());
+        }
+        let animated: Vec<_> = super::animated().map(|solution| solution.day()).collect();
+        assert_eq!(animated, [6, 14, 15, 16, 18]);
+        let pictured: Vec<_> = super::pictured().map(|solution| solution.day()).collect();
+        assert_eq!(pictured, [12, 14, 16, 20]);
+    }
+
+    #[test]
+    fn direction_turns() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.turn().turn_left(), direction);
+            assert_eq!(direction.reverse().reverse(), direction);
+            let (d_row, d_col) = direction.offset();
+            assert_eq!(direction.reverse().offset(), (-d_row, -d_col));
+        }
+        assert_eq!(Direction::North.turn(), Direction::East);
+        assert_eq!(Direction::North.turn_left(), Direction::West);
+        assert_eq!(Direction::NorthEast.turn(), Direction::SouthEast);
+    }
+
+    #[test]
+    fn direction_steps() {
+        assert_eq!(Direction::North.step((0, 3), (5, 5)), None);
+        assert_eq!(Direction::East.step((2, 4), (5, 5)), None);
+        assert_eq!(Direction::SouthWest.step((2, 4), (5, 5)), Some((3, 3)));
+        assert_eq!(Direction::try_from('v').unwrap(), Direction::South);
+        assert!(Direction::try_from('W').is_err());
+        assert!(Direction::try_from('x').is_err());
+        assert_eq!(Direction::from_letter('W').unwrap(), Direction::West);
+        assert!(Direction::from_letter('<').is_err());
+    }
 }
@@ -0,0 +1,780 @@
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "fetch")]
+use std::time::SystemTime;
+use std::time::{Duration, Instant};
+
+use advent2024::report::{self, Report, Run};
+#[cfg(feature = "fetch")]
+use advent2024::site::{self, Client, Reply};
+#[cfg(feature = "fetch")]
+use advent2024::submit::{Outcome, Submitter};
+use advent2024::visual::{self, Animation};
+use advent2024::{bench, input, parallel, Answers, InputLoader, Solution, Timings, Verdict};
+use anyhow::{bail, Context};
+
+const USAGE: &str = "\
+Usage: advent2024 [--check] [--format table|json] <day> [input]
+                                            Solve one day, reading the input file (or stdin for `-`)
+       advent2024 [--check] [--format table|json] [--sequential] all [dir]
+                                            Solve every day, reading <dir>/pN.txt
+       advent2024 inputs <day> <dir|glob>       Solve a day for several inputs, e.g. 'inputs/p5-*.txt'
+       advent2024 bench <day|all> [--runs N] [--format table|csv|json]
+                        [--save file] [--baseline file] [--threshold percent]
+                        [--compare-parallel]
+                                            Time parsing and each part separately
+       advent2024 submit <day> <part> [answer]  Submit an answer, solving the day if none is given
+       advent2024 animate <day> [input] [--delay ms] [--every N] [--dump file] [--plain]
+                                            Play a day's simulation (days 6, 14, 15, 16 and 18)
+       advent2024 image <day> <file.ppm|file.png> [input] [--scale N]
+                                            Draw a day's grid (days 12, 14, 16 and 20)
+
+Inputs are read from $ADVENT_INPUT_DIR/pN.txt, or input/pN.txt, unless given explicitly.
+Missing inputs are downloaded there first if $ADVENT_SESSION holds a session cookie
+(from $ADVENT_BASE_URL, if set, instead of adventofcode.com); downloads and submit need
+a build with the fetch feature.
+submit refuses answers already found wrong (kept in <input dir>/submissions.txt), waits out
+the site's cooldown, and adds correct answers to the answers file.
+all solves the days concurrently; --sequential solves one at a time, for accurate timings.
+--check compares each answer to <input dir>/answers.txt (or $ADVENT_ANSWERS_FILE)
+--format json prints each day's answers, time, input hash and error as a JSON array.
+--save stores the benchmark as CSV, and --baseline flags stages whose median time
+grew by more than --threshold percent compared to a stored file.
+animate pauses --delay milliseconds between frames and shows only every Nth with --every;
+--dump writes the frames to a text file instead, and --plain turns off colors.
+image draws each cell as --scale by --scale pixels; PNG files need the png feature.
+--compare-parallel times each day without and with the thread pool, which needs a build
+with the parallel feature";
+
+/// Runs of each stage when benchmarking, unless `--runs` is given
+const DEFAULT_RUNS: usize = 10;
+/// Pixels per side of each grid cell in images, unless `--scale` is given
+const DEFAULT_SCALE: usize = 4;
+/// Allowed slowdown against a baseline, in percent, unless `--threshold` is given
+const DEFAULT_THRESHOLD: f64 = 10.0;
+
+fn get_solution(day: &str) -> anyhow::Result<&'static dyn Solution> {
+    let number = day
+        .parse()
+        .with_context(|| format!("Invalid day {:?}\n{}", day, USAGE))?;
+    match advent2024::get_day(number) {
+        Some(solution) => Ok(solution),
+        None => bail!("No solution registered for day {}", number),
+    }
+}
+
+fn read_input(
+    loader: &InputLoader,
+    solution: &dyn Solution,
+    path: Option<&str>,
+) -> anyhow::Result<String> {
+    let mut input = String::new();
+    match path {
+        None => {
+            input = loader.load(solution.day()).with_context(|| {
+                let path = loader.path(solution.day());
+                format!("Failed to read input file {}", path.display())
+            })?;
+        }
+        Some("-") => {
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .context("Failed to read input from stdin")?;
+        }
+        Some(path) => {
+            input = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read input file {}", path))?;
+        }
+    }
+    Ok(input)
+}
+
+fn read_answers(loader: &InputLoader) -> anyhow::Result<Answers> {
+    let path = Answers::path(loader);
+    let text = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read answers file {}", path.display()))?;
+    Answers::parse(&text).with_context(|| format!("Invalid answers file {}", path.display()))
+}
+
+/// Counts of each verdict while checking answers
+#[derive(Default)]
+struct Tally {
+    pass: usize,
+    fail: usize,
+    missing: usize,
+}
+
+impl Tally {
+    /// Check an answer if answers are being checked, counting the verdict
+    fn check(
+        &mut self,
+        answers: Option<&Answers>,
+        day: u8,
+        part: u8,
+        answer: &str,
+    ) -> Option<Verdict> {
+        let verdict = answers?.check(day, part, answer);
+        match verdict {
+            Verdict::Pass => self.pass += 1,
+            Verdict::Fail { .. } => self.fail += 1,
+            Verdict::Missing => self.missing += 1,
+        }
+        Some(verdict)
+    }
+    /// Describe an answer, followed by its verdict if it is being checked
+    fn describe(&mut self, answers: Option<&Answers>, day: u8, part: u8, answer: &str) -> String {
+        match self.check(answers, day, part, answer) {
+            Some(verdict) => format!("{} ({})", answer, verdict),
+            None => answer.to_string(),
+        }
+    }
+    /// Check both answers of a run, or count both as failed if the day failed
+    fn check_run(&mut self, answers: Option<&Answers>, day: u8, run: &mut Run) {
+        match &run.answers {
+            Ok((part1, part2)) => {
+                let part1 = self.check(answers, day, 1, part1);
+                let part2 = self.check(answers, day, 2, part2);
+                run.verdicts = part1.zip(part2).map(<[Verdict; 2]>::from);
+            }
+            Err(_) if answers.is_some() => self.fail += 2, // Neither part produced an answer
+            Err(_) => {}
+        }
+    }
+    fn finish(&self, answers: Option<&Answers>) -> anyhow::Result<()> {
+        if answers.is_none() {
+            return Ok(());
+        }
+        println!(
+            "Checked: {} passed, {} failed, {} missing",
+            self.pass, self.fail, self.missing
+        );
+        self.verdict()
+    }
+    /// Fail if any answer was wrong, without printing the counts
+    fn verdict(&self) -> anyhow::Result<()> {
+        if self.fail > 0 {
+            bail!("{} answers do not match the answers file", self.fail);
+        }
+        Ok(())
+    }
+}
+
+fn run_day(
+    loader: &InputLoader,
+    solution: &dyn Solution,
+    path: Option<&str>,
+    answers: Option<&Answers>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let input = read_input(loader, solution, path)?;
+    let mut run = Run::solve(solution, &input);
+    let mut tally = Tally::default();
+    let day = solution.day();
+    if json {
+        tally.check_run(answers, day, &mut run);
+        let error = run.answers.as_ref().err().cloned();
+        let report = Report {
+            day,
+            title: solution.title(),
+            run: Some(run),
+        };
+        print!("{}", report::to_json(&[report]));
+        if let Some(err) = error {
+            return Err(err.into());
+        }
+        return tally.verdict();
+    }
+    let (part1, part2) = run.answers?;
+    println!("Day {}: {}", day, solution.title());
+    println!("Part 1: {}", tally.describe(answers, day, 1, &part1));
+    println!("Part 2: {}", tally.describe(answers, day, 2, &part2));
+    println!("Time: {:.2?}", run.time);
+    tally.finish(answers)
+}
+
+fn run_inputs(day: &str, pattern: &str) -> anyhow::Result<()> {
+    let solution = get_solution(day)?;
+    let paths = input::expand(pattern).with_context(|| format!("Failed to list {}", pattern))?;
+    if paths.is_empty() {
+        bail!("No inputs match {}", pattern);
+    }
+    // Failures are reported in the table, so keep the default hook from printing them too
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let mut rows = Vec::new();
+    let mut failed = 0;
+    for path in paths.iter() {
+        let name = path.display().to_string();
+        let input = match std::fs::read_to_string(path) {
+            Ok(input) => input,
+            Err(err) => {
+                failed += 1;
+                rows.push([
+                    name,
+                    "UNREADABLE".into(),
+                    err.to_string(),
+                    "-".into(),
+                    "-".into(),
+                ]);
+                continue;
+            }
+        };
+        let start = Instant::now();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| solution.solve(&input)));
+        let elapsed = format!("{:.2?}", start.elapsed());
+        let [status, part1, part2] = match result {
+            Ok(Ok((part1, part2))) => ["ok".into(), part1.to_string(), part2.to_string()],
+            Ok(Err(err)) => ["ERROR".into(), err.to_string(), "-".into()],
+            Err(payload) => ["PANIC".into(), report::panic_message(&*payload), "-".into()],
+        };
+        if status != "ok" {
+            failed += 1;
+        }
+        rows.push([name, status, part1, part2, elapsed]);
+    }
+    panic::set_hook(hook);
+    print_table(["Input", "Status", "Part 1", "Part 2", "Time"], &rows);
+    if failed > 0 {
+        bail!("{} of {} inputs failed", failed, paths.len());
+    }
+    Ok(())
+}
+
+/// Solve every day, on a pool of threads unless `sequential`, returning the reports in day order
+fn solve_days(loader: &InputLoader, sequential: bool) -> Vec<Report> {
+    let days = advent2024::DAYS;
+    let run = |solution: &dyn Solution| {
+        let input = loader.load(solution.day()).ok();
+        Report::solve(solution, input.as_deref())
+    };
+    // A panic fails only its own day, which the report shows, so keep the default hook quiet
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let reports = if sequential {
+        days.iter().map(|solution| run(*solution)).collect()
+    } else {
+        solve_concurrently(days, run)
+    };
+    panic::set_hook(hook);
+    reports
+}
+
+/// Solve each day on a pool of threads, returning the reports in day order
+fn solve_concurrently(
+    days: &[&dyn Solution],
+    run: impl Fn(&dyn Solution) -> Report + Sync,
+) -> Vec<Report> {
+    let workers = std::thread::available_parallelism().map_or(1, usize::from);
+    let next = AtomicUsize::new(0);
+    let mut runs: Vec<(usize, Report)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers.min(days.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(solution) = days.get(idx) else {
+                            return done;
+                        };
+                        done.push((idx, run(*solution)));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("days catch their own panics"))
+            .collect()
+    });
+    runs.sort_by_key(|&(idx, _)| idx);
+    runs.into_iter().map(|(_, run)| run).collect()
+}
+
+fn run_all(
+    loader: &InputLoader,
+    answers: Option<&Answers>,
+    sequential: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let mut tally = Tally::default();
+    let mut rows = Vec::new();
+    let mut total = Duration::ZERO;
+    let start = Instant::now();
+    let mut reports = solve_days(loader, sequential);
+    let wall_time = start.elapsed();
+    if json {
+        for report in reports.iter_mut() {
+            if let Some(run) = &mut report.run {
+                tally.check_run(answers, report.day, run);
+            }
+        }
+        print!("{}", report::to_json(&reports));
+        return tally.verdict();
+    }
+    for report in reports {
+        let day = report.day.to_string();
+        let title = report.title.to_string();
+        let Some(run) = report.run else {
+            let missing = "missing input".to_string();
+            rows.push([day, title, "-".into(), "-".into(), missing]);
+            continue;
+        };
+        total += run.time;
+        let (part1, part2) = match &run.answers {
+            Ok((part1, part2)) => (
+                tally.describe(answers, report.day, 1, part1),
+                tally.describe(answers, report.day, 2, part2),
+            ),
+            Err(err) => {
+                if answers.is_some() {
+                    tally.fail += 2; // Neither part produced an answer
+                }
+                (err.to_string(), "-".into())
+            }
+        };
+        rows.push([day, title, part1, part2, format!("{:.2?}", run.time)]);
+    }
+    let total = format!("{:.2?}", total);
+    rows.push(["".into(), "".into(), "".into(), "".into(), total]);
+    print_table(["Day", "Title", "Part 1", "Part 2", "Time"], &rows);
+    if !sequential {
+        println!("Wall time: {:.2?}", wall_time);
+    }
+    tally.finish(answers)
+}
+
+#[cfg(feature = "fetch")]
+fn run_submit(
+    loader: &InputLoader,
+    day: &str,
+    part: &str,
+    answer: Option<&str>,
+) -> anyhow::Result<()> {
+    let solution = get_solution(day)?;
+    let day = solution.day();
+    let part = match part {
+        "1" => 1,
+        "2" => 2,
+        _ => bail!("Invalid part {:?}\n{}", part, USAGE),
+    };
+    let client = Client::from_env()
+        .with_context(|| format!("Set {} to a session cookie to submit", site::SESSION_VAR))?;
+    let answer = match answer {
+        Some(answer) => answer.to_string(),
+        None => {
+            let input = read_input(loader, solution, None)?;
+            let answer = if part == 1 {
+                solution.part1(&input)?
+            } else {
+                solution.part2(&input)?
+            };
+            answer.to_string()
+        }
+    };
+    let submitter = Submitter::for_loader(client, loader);
+    println!("Day {} part {}: submitting {}", day, part, answer);
+    loop {
+        match submitter.submit(day, part, &answer, SystemTime::now())? {
+            Outcome::Cooldown(wait) => {
+                eprintln!(
+                    "Waiting {:.0?} before the site accepts another answer",
+                    wait
+                );
+                std::thread::sleep(wait);
+            }
+            // The submitter has noted the cooldown, so the next attempt waits it out
+            Outcome::Sent(Reply::TooSoon(_)) => {}
+            Outcome::Sent(Reply::Correct) => {
+                let path = Answers::path(loader);
+                println!("Right answer, recorded in {}", path.display());
+                return Ok(());
+            }
+            Outcome::Sent(Reply::Wrong { hint, .. }) => match hint {
+                Some(hint) => bail!("Wrong answer, {}", hint),
+                None => bail!("Wrong answer"),
+            },
+            Outcome::Sent(Reply::AlreadySolved) => {
+                println!("Already solved, but the answer is not in the answers file");
+                return Ok(());
+            }
+            Outcome::Sent(Reply::Unknown(text)) => bail!("Unexpected reply: {}", text),
+            Outcome::Verified { expected } if expected == answer => {
+                println!("Already verified");
+                return Ok(());
+            }
+            Outcome::Verified { expected } => {
+                bail!("Not sent, the verified answer is {}", expected)
+            }
+            Outcome::KnownWrong => bail!("Not sent, this answer was already wrong"),
+        }
+    }
+}
+
+/// Output format of a benchmark run
+enum Format {
+    Table,
+    Csv,
+    Json,
+}
+
+struct BenchOptions {
+    runs: usize,
+    format: Format,
+    save: Option<String>,
+    baseline: Option<String>,
+    threshold: f64,
+    compare_parallel: bool,
+}
+
+impl BenchOptions {
+    fn from_args(args: &mut Vec<String>) -> anyhow::Result<Self> {
+        let runs = match take_option(args, "--runs")? {
+            Some(runs) => runs
+                .parse()
+                .with_context(|| format!("Invalid number of runs {:?}", runs))?,
+            None => DEFAULT_RUNS,
+        };
+        let format = match take_option(args, "--format")?.as_deref() {
+            None | Some("table") => Format::Table,
+            Some("csv") => Format::Csv,
+            Some("json") => Format::Json,
+            Some(format) => bail!("Unknown format {:?}\n{}", format, USAGE),
+        };
+        let threshold = match take_option(args, "--threshold")? {
+            Some(threshold) => threshold
+                .trim_end_matches('%')
+                .parse()
+                .with_context(|| format!("Invalid threshold {:?}", threshold))?,
+            None => DEFAULT_THRESHOLD,
+        };
+        Ok(Self {
+            runs,
+            format,
+            save: take_option(args, "--save")?,
+            baseline: take_option(args, "--baseline")?,
+            threshold,
+            compare_parallel: take_flag(args, "--compare-parallel"),
+        })
+    }
+}
+
+fn read_baseline(path: &str) -> anyhow::Result<Vec<(u8, Timings)>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read baseline file {}", path))?;
+    bench::from_csv(&text).with_context(|| format!("Invalid baseline file {}", path))
+}
+
+/// Benchmark one day, or every day with an input that can be solved
+fn bench_days(loader: &InputLoader, days: &str, runs: usize) -> anyhow::Result<Vec<(u8, Timings)>> {
+    let solutions = match days {
+        "all" => advent2024::DAYS.to_vec(),
+        day => vec![get_solution(day)?],
+    };
+    let mut results: Vec<(u8, Timings)> = Vec::new();
+    for solution in solutions {
+        let day = solution.day();
+        let input = match read_input(loader, solution, None) {
+            Ok(input) => input,
+            Err(err) if days == "all" => {
+                eprintln!("Skipping day {}, {:#}", day, err);
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        match solution.bench(&input, runs) {
+            Ok(timings) => results.push((day, timings)),
+            Err(err) if days == "all" => eprintln!("Skipping day {}, {}", day, err),
+            Err(err) => bail!("Failed to benchmark day {}: {}", day, err),
+        }
+    }
+    Ok(results)
+}
+
+/// Time each day with the thread pool switched off, then on, and compare the medians
+fn run_parallel_comparison(loader: &InputLoader, days: &str, runs: usize) -> anyhow::Result<()> {
+    if !cfg!(feature = "parallel") {
+        bail!("--compare-parallel needs a build with `--features parallel`");
+    }
+    parallel::set_enabled(false);
+    let sequential = bench_days(loader, days, runs)?;
+    parallel::set_enabled(true);
+    let threaded = bench_days(loader, days, runs)?;
+    let mut rows = Vec::new();
+    for ((day, before), (_, after)) in sequential.iter().zip(threaded.iter()) {
+        for ((stage, before), (_, after)) in before.stages().into_iter().zip(after.stages()) {
+            let speedup = before.median.as_secs_f64() / after.median.as_secs_f64().max(1e-9);
+            rows.push([
+                day.to_string(),
+                stage.to_string(),
+                format!("{:.2?}", before.median),
+                format!("{:.2?}", after.median),
+                format!("{:.2}x", speedup),
+            ]);
+        }
+    }
+    print_table(["Day", "Stage", "Sequential", "Parallel", "Speedup"], &rows);
+    Ok(())
+}
+
+fn run_bench(loader: &InputLoader, days: &str, options: &BenchOptions) -> anyhow::Result<()> {
+    if options.compare_parallel {
+        return run_parallel_comparison(loader, days, options.runs);
+    }
+    // Read the baseline first, so a bad path fails before the slow part
+    let baseline = options.baseline.as_deref().map(read_baseline).transpose()?;
+    let results = bench_days(loader, days, options.runs)?;
+    match options.format {
+        Format::Csv => print!("{}", bench::to_csv(&results)),
+        Format::Json => print!("{}", bench::to_json(&results)),
+        Format::Table => {
+            let mut rows = Vec::new();
+            for (day, timings) in results.iter() {
+                for (stage, stats) in timings.stages() {
+                    rows.push([
+                        day.to_string(),
+                        stage.to_string(),
+                        stats.runs.to_string(),
+                        format!("{:.2?}", stats.min),
+                        format!("{:.2?}", stats.median),
+                        format!("{:.2?}", stats.mean),
+                        format!("{:.2?}", stats.stddev),
+                    ]);
+                }
+            }
+            let header = ["Day", "Stage", "Runs", "Min", "Median", "Mean", "Stddev"];
+            print_table(header, &rows);
+        }
+    }
+    if let Some(path) = &options.save {
+        std::fs::write(path, bench::to_csv(&results))
+            .with_context(|| format!("Failed to write baseline file {}", path))?;
+        eprintln!("Saved baseline to {}", path);
+    }
+    let Some(baseline) = baseline else {
+        return Ok(());
+    };
+    let found = bench::regressions(&baseline, &results, options.threshold);
+    for regression in found.iter() {
+        eprintln!(
+            "Day {} {}: {:.2?} -> {:.2?} (+{:.1}%)",
+            regression.day,
+            regression.stage,
+            regression.baseline,
+            regression.current,
+            regression.percent()
+        );
+    }
+    if !found.is_empty() {
+        bail!(
+            "{} stages regressed by more than {}%",
+            found.len(),
+            options.threshold
+        );
+    }
+    eprintln!("No stage regressed by more than {}%", options.threshold);
+    Ok(())
+}
+
+struct AnimateOptions {
+    delay: Duration,
+    every: usize,
+    dump: Option<String>,
+    plain: bool,
+}
+
+impl AnimateOptions {
+    fn from_args(args: &mut Vec<String>) -> anyhow::Result<Self> {
+        let delay = match take_option(args, "--delay")? {
+            Some(delay) => delay
+                .parse()
+                .map(Duration::from_millis)
+                .with_context(|| format!("Invalid delay {:?}", delay))?,
+            None => visual::DEFAULT_DELAY,
+        };
+        let every = match take_option(args, "--every")? {
+            Some(every) => every
+                .parse()
+                .with_context(|| format!("Invalid frame interval {:?}", every))?,
+            None => 1,
+        };
+        Ok(Self {
+            delay,
+            every,
+            dump: take_option(args, "--dump")?,
+            plain: take_flag(args, "--plain"),
+        })
+    }
+}
+
+fn run_animate(
+    loader: &InputLoader,
+    day: &str,
+    path: Option<&str>,
+    options: &AnimateOptions,
+) -> anyhow::Result<()> {
+    let solution = get_solution(day)?;
+    let Some(animate) = solution.animate() else {
+        let days: Vec<_> = advent2024::animated()
+            .map(|solution| solution.day().to_string())
+            .collect();
+        bail!(
+            "Day {} has no animation, try day {}",
+            solution.day(),
+            days.join(", ")
+        );
+    };
+    let input = read_input(loader, solution, path)?;
+    let mut animation = match &options.dump {
+        Some(dump) => Animation::to_file(dump)
+            .with_context(|| format!("Failed to create frames file {}", dump))?,
+        None => Animation::terminal()
+            .delay(options.delay)
+            .colored(!options.plain),
+    }
+    .every(options.every);
+    animate(&input, &mut animation)?;
+    let frames = animation.finish().context("Failed to write frames")?;
+    if let Some(dump) = &options.dump {
+        println!("Wrote {} frames to {}", frames, dump);
+    }
+    Ok(())
+}
+
+fn run_image(
+    loader: &InputLoader,
+    day: &str,
+    file: &str,
+    path: Option<&str>,
+    scale: usize,
+) -> anyhow::Result<()> {
+    let solution = get_solution(day)?;
+    let Some(picture) = solution.picture() else {
+        let days: Vec<_> = advent2024::pictured()
+            .map(|solution| solution.day().to_string())
+            .collect();
+        bail!(
+            "Day {} has no image, try day {}",
+            solution.day(),
+            days.join(", ")
+        );
+    };
+    let input = read_input(loader, solution, path)?;
+    let image = picture(&input)?.scale(scale);
+    image
+        .save(file)
+        .with_context(|| format!("Failed to save image {}", file))?;
+    println!(
+        "Wrote a {}x{} image to {}",
+        image.width(),
+        image.height(),
+        file
+    );
+    Ok(())
+}
+
+fn print_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let mut widths = header.map(str::len);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = std::cmp::max(*width, cell.len());
+        }
+    }
+    let print_row = |cells: [&str; N]| {
+        let line: Vec<_> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        println!("{}", line.join(" | ").trim_end());
+    };
+    print_row(header);
+    println!("{}", widths.map(|width| "-".repeat(width)).join("-|-"));
+    for row in rows {
+        print_row(row.each_ref().map(String::as_str));
+    }
+}
+
+/// Remove a flag from the arguments, returning whether it was there
+fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    let found = args.iter().any(|arg| arg == name);
+    args.retain(|arg| arg != name);
+    found
+}
+
+/// Remove `--name value` from the arguments, returning the value
+fn take_option(args: &mut Vec<String>, name: &str) -> anyhow::Result<Option<String>> {
+    let Some(idx) = args.iter().position(|arg| arg == name) else {
+        return Ok(None);
+    };
+    if idx + 1 >= args.len() {
+        bail!("Missing value for {}\n{}", name, USAGE);
+    }
+    let value = args.remove(idx + 1);
+    args.remove(idx);
+    Ok(Some(value))
+}
+
+fn main() -> anyhow::Result<()> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let check = take_flag(&mut args, "--check");
+    let sequential = take_flag(&mut args, "--sequential");
+    let options = BenchOptions::from_args(&mut args)?;
+    let animate_options = AnimateOptions::from_args(&mut args)?;
+    let json = matches!(options.format, Format::Json);
+    let scale = match take_option(&mut args, "--scale")? {
+        Some(scale) => scale
+            .parse()
+            .with_context(|| format!("Invalid scale {:?}", scale))?,
+        None => DEFAULT_SCALE,
+    };
+    let arg = |idx: usize| args.get(idx).map(String::as_str);
+    if matches!(options.format, Format::Csv) && arg(0) != Some("bench") {
+        bail!("CSV output is only for bench\n{}", USAGE);
+    }
+    if json && matches!(arg(0), Some("inputs" | "animate" | "image" | "submit")) {
+        bail!("JSON output is only for solving days and bench\n{}", USAGE);
+    }
+    let loader = match (arg(0), arg(1)) {
+        (Some("all"), Some(dir)) => InputLoader::new(dir),
+        _ => InputLoader::from_env(),
+    };
+    #[cfg(feature = "fetch")]
+    let loader = match Client::from_env() {
+        Some(client) => loader.with_client(client),
+        None => loader,
+    };
+    let answers = if check {
+        Some(read_answers(&loader)?)
+    } else {
+        None
+    };
+    match arg(0) {
+        Some("all") => run_all(&loader, answers.as_ref(), sequential, json),
+        Some("bench") => match arg(1) {
+            Some(days) => run_bench(&loader, days, &options),
+            None => bail!("{}", USAGE),
+        },
+        Some("inputs") => match (arg(1), arg(2)) {
+            (Some(day), Some(pattern)) => run_inputs(day, pattern),
+            _ => bail!("{}", USAGE),
+        },
+        Some("animate") => match arg(1) {
+            Some(day) => run_animate(&loader, day, arg(2), &animate_options),
+            None => bail!("{}", USAGE),
+        },
+        Some("image") => match (arg(1), arg(2)) {
+            (Some(day), Some(file)) => run_image(&loader, day, file, arg(3), scale),
+            _ => bail!("{}", USAGE),
+        },
+        Some("submit") => match (arg(1), arg(2)) {
+            #[cfg(feature = "fetch")]
+            (Some(day), Some(part)) => run_submit(&loader, day, part, arg(3)),
+            #[cfg(not(feature = "fetch"))]
+            (Some(_), Some(_)) => bail!("submit needs a build with `--features fetch`"),
+            _ => bail!("{}", USAGE),
+        },
+        Some(day) => run_day(&loader, get_solution(day)?, arg(1), answers.as_ref(), json),
+        None => bail!("{}", USAGE),
+    }
+}
@@ -1,43 +1,82 @@
+use crate::error::parse_token;
+use crate::{answer, answers, Answer, Error, Result, Solution, Timings};
 use itertools::Itertools;
 
-pub fn solve(input: &str) -> Option<(i32, i32)> {
+#[derive(Clone)]
+struct Lists {
+    left: Vec<i32>,
+    right: Vec<i32>,
+}
+
+fn checked_sum(values: impl IntoIterator<Item = Option<i64>>) -> Result<i64> {
+    values
+        .into_iter()
+        .try_fold(0i64, |total, value| total.checked_add(value?))
+        .ok_or_else(|| Error::no_solution("the total does not fit in 64 bits"))
+}
+
+impl Lists {
+    fn total_distance(&self) -> Result<i64> {
+        // No two IDs are further apart than 64 bits can hold
+        checked_sum(
+            self.left
+                .iter()
+                .zip(&self.right)
+                .map(|(&a, &b)| Some((i64::from(a) - i64::from(b)).abs())),
+        )
+    }
+    fn similarity(&self) -> Result<i64> {
+        /*
+        This is more complicated than it needs to be, but with a sorted list
+        we can binary search efficiently without needing to create a secondary
+        data structure.
+        */
+
+        let counts = self.left.iter().map(|x| {
+            self.right
+                .binary_search(x)
+                .map(|idx| {
+                    let real_st = walk(idx, &self.right);
+                    count(real_st, &self.right)
+                })
+                .unwrap_or(0)
+        });
+        checked_sum(
+            self.left
+                .iter()
+                .zip(counts)
+                .map(|(&a, b)| i64::from(a).checked_mul(i64::try_from(b).ok()?)),
+        )
+    }
+}
+
+fn parse(input: &str) -> Result<Lists> {
     let mut left: Vec<i32> = Vec::new();
     let mut right: Vec<i32> = Vec::new();
-    for line in input.lines() {
-        let (a, b) = line.split_whitespace().collect_tuple()?;
-        left.push(a.parse().ok()?);
-        right.push(b.parse().ok()?)
+    for (line_idx, line) in input.lines().enumerate() {
+        let (a, b) = line
+            .split_whitespace()
+            .collect_tuple()
+            .ok_or_else(|| Error::parse("expected two location IDs").at_line(line_idx))?;
+        left.push(parse_token(line_idx, line, a)?);
+        right.push(parse_token(line_idx, line, b)?);
     }
     left.sort();
     right.sort();
-    let part1: i32 = left
-        .iter()
-        .copied()
-        .zip(right.iter().copied())
-        .map(|(a, b)| (a - b).abs())
-        .sum();
-    /*
-    This is more complicated than it needs to be, but with a sorted list
-    we can binary search efficiently without needing to create a secondary
-    data structure.
-    */
-
-    let counts = left.iter().map(|x| {
-        right
-            .binary_search(x)
-            .map(|idx| {
-                let real_st = walk(idx, &right);
-                count(real_st, &right)
-            })
-            .unwrap_or(0)
-    });
-    let part2 = left
-        .iter()
-        .copied()
-        .zip(counts)
-        .map(|(a, b)| a * b as i32)
-        .sum();
-    Some((part1, part2))
+    Ok(Lists { left, right })
+}
+
+pub fn part1(input: &str) -> Result<i64> {
+    parse(input)?.total_distance()
+}
+
+pub fn part2(input: &str) -> Result<i64> {
+    parse(input)?.similarity()
+}
+
+pub fn solve(input: &str) -> Result<(i64, i64)> {
+    let lists = parse(input)?;
+    Ok((lists.total_distance()?, lists.similarity()?))
 }
 
 fn count(st: usize, slice: &[i32]) -> usize {
@@ -56,11 +95,106 @@ fn walk(st: usize, slice: &[i32]) -> usize {
         .unwrap()
 }
 
+pub struct Day1;
+
+impl Solution for Day1 {
+    fn day(&self) -> u8 {
+        1
+    }
+    fn title(&self) -> &'static str {
+        "Historian Hysteria"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |lists| lists.total_distance(),
+            |lists| lists.similarity(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p1.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+3   4
+4   3
+2   5
+1   3
+3   9
+3   3
+";
+
+    #[test]
+    fn day1_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 11);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 31);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (11, 31));
+    }
+
     #[test]
     fn day1_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(1) else {
+            return;
+        };
+        crate::answers::assert_recorded(1, super::solve(&input).unwrap());
+    }
+
+    #[test]
+    fn day1_large_ids() {
+        assert_eq!(super::part1("2147483647   -1\n").unwrap(), 2147483648);
+        let input = "2147483647   2147483647\n".repeat(2);
+        assert_eq!(super::solve(&input).unwrap(), (0, 8589934588));
+    }
+
+    /// Pair up the sorted lists and count every match by brute force
+    fn naive(left: &[i32], right: &[i32]) -> (i64, i64) {
+        let mut sorted_left = left.to_vec();
+        let mut sorted_right = right.to_vec();
+        sorted_left.sort();
+        sorted_right.sort();
+        let distance = sorted_left
+            .iter()
+            .zip(&sorted_right)
+            .map(|(&a, &b)| (i64::from(a) - i64::from(b)).abs())
+            .sum();
+        let similarity = left
+            .iter()
+            .map(|&a| i64::from(a) * right.iter().filter(|&&b| b == a).count() as i64)
+            .sum();
+        (distance, similarity)
+    }
+
+    proptest! {
+        #[test]
+        fn day1_matches_naive(pairs in prop::collection::vec((0..20, 0..20), 0..40)) {
+            // Few distinct IDs, so the binary search lands inside runs of duplicates
+            let (left, right): (Vec<i32>, Vec<i32>) = pairs.into_iter().unzip();
+            let input: String = left
+                .iter()
+                .zip(&right)
+                .map(|(a, b)| format!("{}   {}\n", a, b))
+                .collect();
+            prop_assert_eq!(super::solve(&input).unwrap(), naive(&left, &right));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn day1_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
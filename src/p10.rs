@@ -1,83 +1,144 @@
-#[derive(Debug)]
-struct SquareGrid {
-    data: Vec<u8>,
-    length: usize,
-}
-impl SquareGrid {
-    fn new(data: Vec<u8>, length: usize) -> Self {
-        Self { data, length }
-    }
-    /// Returns scores for part1 and part2, respectively
-    fn score(&self, idx: usize) -> (usize, usize) {
-        let mut buffer = Vec::new();
-        let position = (idx / self.length, idx % self.length);
-        self.dfs(1, position, &mut buffer);
-        let part2 = buffer.len();
-        // Achieve set-like behavior
-        buffer.sort();
-        buffer.dedup();
-        let part1 = buffer.len();
-        (part1, part2)
+use crate::{answer, answers, Answer, Error, Grid, Result, Solution, Timings};
+
+/// Sums the trailhead scores for part1 and part2, respectively. Both come from the same search
+fn total_score(grid: &Grid<u8>) -> (usize, usize) {
+    let mut part1 = 0;
+    let mut part2 = 0;
+    for (position, _) in grid.iter().filter(|(_, height)| **height == 0) {
+        let (p1, p2) = score(grid, position);
+        part1 += p1;
+        part2 += p2;
     }
-    fn dfs(&self, goal: u8, position: (usize, usize), data: &mut Vec<(usize, usize)>) {
-        if goal == 10 {
-            data.push(position); // Already checked correctness in previous parent function call
-        }
-        let (row, col) = position;
-        let neighbors = [
-            (row - 1, col),
-            (row, col + 1),
-            (row + 1, col),
-            (row, col - 1),
-        ];
-        for n in neighbors {
-            let (row, col) = n;
-            if let Some(sq) = self.get(row, col) {
-                if sq == goal {
-                    self.dfs(goal + 1, n, data);
-                }
-            }
-        }
+    (part1, part2)
+}
+
+/// Returns scores for part1 and part2, respectively
+fn score(grid: &Grid<u8>, position: (usize, usize)) -> (usize, usize) {
+    let mut buffer = Vec::new();
+    dfs(grid, 1, position, &mut buffer);
+    let part2 = buffer.len();
+    // Achieve set-like behavior
+    buffer.sort();
+    buffer.dedup();
+    let part1 = buffer.len();
+    (part1, part2)
+}
+
+fn dfs(grid: &Grid<u8>, goal: u8, position: (usize, usize), data: &mut Vec<(usize, usize)>) {
+    if goal == 10 {
+        data.push(position); // Already checked correctness in previous parent function call
     }
-    fn get(&self, row: usize, col: usize) -> Option<u8> {
-        if row >= self.length || col >= self.length {
-            None
-        } else {
-            Some(self.data[row * self.length + col])
+    for n in grid.neighbors(position) {
+        if grid[n] == goal {
+            dfs(grid, goal + 1, n, data);
         }
     }
 }
 
-pub fn solve(input: &str) -> Option<(usize, usize)> {
-    let mut vec = Vec::new();
-    let mut length = 0;
-    for line in input.lines() {
-        for b in line.as_bytes() {
-            vec.push(*b - b'0');
-        }
-        length += 1;
+/// Height of impassable tiles, which never lie on a trail
+const IMPASSABLE: u8 = u8::MAX;
+
+fn parse(input: &str) -> Result<Grid<u8>> {
+    let grid = Grid::parse(input, |_, c| match c {
+        '0'..='9' => Ok(c as u8 - b'0'),
+        '.' => Ok(IMPASSABLE),
+        _ => Err(Error::unexpected_char(c)),
+    })?;
+    Ok(grid)
+}
+
+pub fn part1(input: &str) -> Result<usize> {
+    Ok(total_score(&parse(input)?).0)
+}
+
+pub fn part2(input: &str) -> Result<usize> {
+    Ok(total_score(&parse(input)?).1)
+}
+
+pub fn solve(input: &str) -> Result<(usize, usize)> {
+    Ok(total_score(&parse(input)?))
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    fn day(&self) -> u8 {
+        10
     }
-    assert_eq!(length * length, vec.len()); // Square grid;
-    let grid = SquareGrid::new(vec, length);
-    let mut part1 = 0;
-    let mut part2 = 0;
-    for (p1, p2) in
-        grid.data
-            .iter()
-            .enumerate()
-            .filter_map(|(i, v)| if *v == 0 { Some(grid.score(i)) } else { None })
-    {
-        part1 += p1;
-        part2 += p2;
+    fn title(&self) -> &'static str {
+        "Hoof It"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |grid| total_score(&grid).0,
+            |grid| total_score(&grid).1,
+        )
     }
-    Some((part1, part2))
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p10.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732
+";
+
+    const EXAMPLE_WIDE: &str = "\
+89010123..
+78121874..
+87430965..
+96549874..
+45678903..
+32019012..
+01329801..
+10456732..
+";
+
+    #[test]
+    fn day10_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 36);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 81);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (36, 81));
+    }
+
+    #[test]
+    fn day10_rectangular() {
+        // Impassable padding does not change any trail
+        assert_eq!(super::solve(EXAMPLE_WIDE).unwrap(), (36, 81));
+    }
+
     #[test]
     fn day10_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(10) else {
+            return;
+        };
+        crate::answers::assert_recorded(10, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day10_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,3 +1,6 @@
+use crate::error::parse_token;
+use crate::{answer, answers, Answer, Error, Result, Solution, Timings};
+
 #[derive(Debug, Clone, Copy, Hash)]
 struct Stone {
     number: u64,
@@ -8,67 +11,208 @@ impl Stone {
     fn new(number: u64, quantity: u64) -> Self {
         Self { number, quantity }
     }
-    fn blink(&mut self) -> Option<Self> {
+    /// Change the stone, returning the right half if it splits
+    fn blink(&mut self) -> Result<Option<Self>> {
         if self.number == 0 {
             self.number = 1;
-            return None;
+            return Ok(None);
         }
         let num_digits = self.number.ilog10() + 1;
-        if num_digits % 2 == 0 {
+        if num_digits.is_multiple_of(2) {
             let pt = 10u64.pow(num_digits / 2);
             let out = Some(Stone::new(self.number % pt, self.quantity));
-            self.number = self.number / pt;
-            out
+            self.number /= pt;
+            Ok(out)
         } else {
-            self.number *= 2024;
-            None
+            self.number = self.number.checked_mul(2024).ok_or_else(too_large)?;
+            Ok(None)
         }
     }
 }
 
-fn compact(stones: &mut Vec<Stone>) {
+fn too_large() -> Error {
+    Error::no_solution("the stones do not fit in 64 bits")
+}
+
+fn compact(stones: &mut Vec<Stone>) -> Result<()> {
     // The vast majority of the stones will end up being single digit, so compact them together
     let mut temp: Vec<Stone> = (0..10).map(|x| Stone::new(x, 0)).collect();
     for s in stones.iter() {
         if s.number < 10 {
-            temp[s.number as usize].quantity += s.quantity;
+            let quantity = &mut temp[s.number as usize].quantity;
+            *quantity = quantity.checked_add(s.quantity).ok_or_else(too_large)?;
         }
     }
     stones.retain(|s| s.number >= 10);
     stones.extend(temp.into_iter().filter(|s| s.quantity > 0));
+    Ok(())
 }
 
-pub fn solve(input: &str) -> Option<(u64, u64)> {
-    let mut remaining: Vec<_> = input
-        .lines()
-        .nth(0)
-        .unwrap()
-        .split_ascii_whitespace()
-        .map(|x| Stone::new(x.parse().unwrap(), 1))
-        .collect();
-
-    let mut part1 = 0;
-    for epoch in 0..75 {
+fn parse(input: &str) -> Result<Vec<Stone>> {
+    let line = input.lines().next().unwrap_or_default();
+    line.split_ascii_whitespace()
+        .map(|x| Ok(Stone::new(parse_token(0, line, x)?, 1)))
+        .collect()
+}
+
+fn blink_all(remaining: &mut Vec<Stone>, epochs: usize) -> Result<()> {
+    for _ in 0..epochs {
         let len = remaining.len();
         for i in 0..len {
-            let right = remaining[i].blink();
+            let right = remaining[i].blink()?;
             if let Some(right) = right {
                 remaining.push(right);
             }
         }
-        compact(&mut remaining);
-        if epoch == 24 {
-            part1 = remaining.iter().map(|s| s.quantity).sum();
-        }
+        compact(remaining)?;
+    }
+    Ok(())
+}
+
+fn count(stones: &[Stone]) -> Result<u64> {
+    stones
+        .iter()
+        .try_fold(0u64, |total, s| total.checked_add(s.quantity))
+        .ok_or_else(too_large)
+}
+
+pub fn part1(input: &str) -> Result<u64> {
+    let mut remaining = parse(input)?;
+    blink_all(&mut remaining, 25)?;
+    count(&remaining)
+}
+
+pub fn part2(input: &str) -> Result<u64> {
+    let mut remaining = parse(input)?;
+    blink_all(&mut remaining, 75)?;
+    count(&remaining)
+}
+
+pub fn solve(input: &str) -> Result<(u64, u64)> {
+    let mut remaining = parse(input)?;
+    blink_all(&mut remaining, 25)?;
+    let part1 = count(&remaining)?;
+    // Part 2 just keeps blinking from where part 1 left off
+    blink_all(&mut remaining, 50)?;
+    Ok((part1, count(&remaining)?))
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    fn day(&self) -> u8 {
+        11
+    }
+    fn title(&self) -> &'static str {
+        "Plutonian Pebbles"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |mut remaining| {
+                blink_all(&mut remaining, 25)?;
+                count(&remaining)
+            },
+            |mut remaining| {
+                blink_all(&mut remaining, 75)?;
+                count(&remaining)
+            },
+        )
     }
-    Some((part1, remaining.iter().map(|s| s.quantity).sum()))
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p11.txt");
+    use super::{blink_all, compact, count, Stone};
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "125 17";
+
+    #[test]
+    fn day11_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 55312);
+        // No published answer for part 2, pinned to catch regressions
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 65601038650482);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (55312, 65601038650482));
+    }
+
     #[test]
     fn day11_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(11) else {
+            return;
+        };
+        crate::answers::assert_recorded(11, super::solve(&input).unwrap());
+    }
+
+    #[test]
+    fn day11_too_large() {
+        // 19 digits, which no longer fit once multiplied by 2024
+        let err = super::solve("9999999999999999999").unwrap_err();
+        assert!(matches!(err.kind(), crate::ErrorKind::NoSolution(_)));
+    }
+
+    /// Keep every stone separately, in order
+    fn naive(mut stones: Vec<u64>, blinks: usize) -> u64 {
+        for _ in 0..blinks {
+            stones = stones
+                .into_iter()
+                .flat_map(|stone| {
+                    let digits = stone.to_string();
+                    if stone == 0 {
+                        vec![1]
+                    } else if digits.len().is_multiple_of(2) {
+                        let (left, right) = digits.split_at(digits.len() / 2);
+                        vec![left.parse().unwrap(), right.parse().unwrap()]
+                    } else {
+                        vec![stone * 2024]
+                    }
+                })
+                .collect();
+        }
+        stones.len() as u64
+    }
+
+    proptest! {
+        #[test]
+        fn day11_matches_naive(numbers in prop::collection::vec(0..10000u64, 1..6), blinks in 0..12usize) {
+            let mut stones: Vec<_> = numbers.iter().map(|&number| Stone::new(number, 1)).collect();
+            blink_all(&mut stones, blinks).unwrap();
+            prop_assert_eq!(count(&stones).unwrap(), naive(numbers, blinks));
+        }
+
+        #[test]
+        fn compact_keeps_quantities(stones in prop::collection::vec((0..20u64, 1..5u64), 0..20)) {
+            let total = |stones: &[Stone], number| -> u64 {
+                stones.iter().filter(|s| s.number == number).map(|s| s.quantity).sum()
+            };
+            let before: Vec<_> = stones.iter().map(|&(number, quantity)| Stone::new(number, quantity)).collect();
+            let mut after = before.clone();
+            compact(&mut after).unwrap();
+            for number in 0..20 {
+                prop_assert_eq!(total(&after, number), total(&before, number));
+            }
+            // Single digits share one stone each
+            for digit in 0..10 {
+                prop_assert!(after.iter().filter(|s| s.number == digit).count() <= 1);
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn day11_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
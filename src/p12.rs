@@ -1,136 +1,236 @@
-use super::Direction;
+use super::image::{Image, Picture, Rgb};
+use super::{answer, answers, Answer, Direction, Error, Grid, Result, Solution, Timings};
 use std::collections::{HashMap, HashSet};
 
 type Fences = [bool; 4];
 
-#[derive(Debug)]
-struct SquareGrid {
-    data: Vec<u8>,
-    length: usize,
-}
-impl SquareGrid {
-    fn new(data: Vec<u8>, length: usize) -> Self {
-        Self { data, length }
-    }
-    /// Returns score for both parts
-    fn score(&self) -> (u64, u64) {
-        let mut global_visited = HashSet::new();
-        let mut cost = 0;
-        let mut cost2 = 0;
-        for row in 0..self.length {
-            for col in 0..self.length {
-                if !global_visited.contains(&(row, col)) {
-                    let mut visited = HashSet::new();
-                    let val = self.get(row, col).unwrap();
-                    let perimeter = self.dfs(val, (row, col), &mut visited);
-                    let area = (visited.len()) as u64;
-                    let sides = self.get_sides(val, &visited);
-                    global_visited.extend(visited.into_iter());
-                    cost += perimeter * area;
-                    cost2 += sides * area;
-                }
+/// Returns the fence cost, priced by sides instead of perimeter with the bulk discount of part 2
+fn score(grid: &Grid<u8>, bulk_discount: bool) -> u64 {
+    let mut global_visited = HashSet::new();
+    let mut cost = 0;
+    for (position, &val) in grid.iter() {
+        if !global_visited.contains(&position) {
+            let mut visited = HashSet::new();
+            let perimeter = dfs(grid, val, position, &mut visited);
+            let area = (visited.len()) as u64;
+            if bulk_discount {
+                cost += get_sides(grid, val, &visited) * area;
+            } else {
+                cost += perimeter * area;
             }
+            global_visited.extend(visited);
         }
-        (cost, cost2)
-    }
-    fn get_sides(&self, token: u8, visited: &HashSet<(usize, usize)>) -> u64 {
-        let mut marks: HashMap<(usize, usize), Fences> = visited
-            .iter()
-            .copied()
-            .map(|position| (position, [false, false, false, false]))
-            .collect();
-        let mut sides = 0;
-        for &(row, col) in visited {
-            let directions = [
-                Direction::North,
-                Direction::East,
-                Direction::South,
-                Direction::West,
-            ];
-            let fences = marks.get(&(row, col)).unwrap().clone();
-            for direction in directions.iter().copied() {
-                if fences[direction as usize] {
-                    continue; // Already built the fence
-                }
-                let n = direction.get_square((row, col));
-                if self.get(n.0, n.1) != Some(token) {
-                    // Needs a fence
-                    sides += 1;
-                    // Raycast perpendicular to set fence status on neighbors
-                    let (perp1, perp2) = direction.get_perp();
-                    for perp in [perp1, perp2] {
-                        let mut square = (row, col);
-                        while let Some(m) = marks.get_mut(&square) {
-                            m[direction as usize] = true;
-                            square = perp.get_square(square);
-                            // Ensure square needs this fence too, i.e. the fence would be contiguous
-                            let n = direction.get_square(square);
-                            if self.get(n.0, n.1) == Some(token) {
-                                break;
-                            }
+    }
+    cost
+}
+
+fn get_sides(grid: &Grid<u8>, token: u8, visited: &HashSet<(usize, usize)>) -> u64 {
+    let mut marks: HashMap<(usize, usize), Fences> = visited
+        .iter()
+        .copied()
+        .map(|position| (position, [false, false, false, false]))
+        .collect();
+    let mut sides = 0;
+    for &(row, col) in visited {
+        let fences = *marks.get(&(row, col)).unwrap();
+        for direction in Direction::CARDINAL {
+            if fences[direction as usize] {
+                continue; // Already built the fence
+            }
+            let n = direction.get_square((row, col));
+            if grid.get(n) != Some(&token) {
+                // Needs a fence
+                sides += 1;
+                // Raycast perpendicular to set fence status on neighbors
+                let (perp1, perp2) = direction.get_perp();
+                for perp in [perp1, perp2] {
+                    let mut square = (row, col);
+                    while let Some(m) = marks.get_mut(&square) {
+                        m[direction as usize] = true;
+                        square = perp.get_square(square);
+                        // Ensure square needs this fence too, i.e. the fence would be contiguous
+                        let n = direction.get_square(square);
+                        if grid.get(n) == Some(&token) {
+                            break;
                         }
                     }
                 }
             }
         }
-        sides
-    }
-    fn dfs(
-        &self,
-        goal: u8,
-        position: (usize, usize),
-        visited: &mut HashSet<(usize, usize)>,
-    ) -> u64 {
-        visited.insert(position);
-        let (row, col) = position;
-        let neighbors = [
-            (row - 1, col),
-            (row, col + 1),
-            (row + 1, col),
-            (row, col - 1),
-        ];
-        let mut perimeter = 4;
-        for n in neighbors {
-            let (row, col) = n;
-            if let Some(sq) = self.get(row, col) {
-                if sq == goal {
-                    perimeter -= 1; // They share an "edge"
-                    if !visited.contains(&(row, col)) {
-                        perimeter += self.dfs(goal, n, visited);
-                    }
-                }
+    }
+    sides
+}
+
+fn dfs(
+    grid: &Grid<u8>,
+    goal: u8,
+    position: (usize, usize),
+    visited: &mut HashSet<(usize, usize)>,
+) -> u64 {
+    visited.insert(position);
+    let mut perimeter = 4;
+    for n in grid.neighbors(position) {
+        if grid[n] == goal {
+            perimeter -= 1; // They share an "edge"
+            if !visited.contains(&n) {
+                perimeter += dfs(grid, goal, n, visited);
             }
         }
-        perimeter
     }
-    fn get(&self, row: usize, col: usize) -> Option<u8> {
-        if row >= self.length || col >= self.length {
-            None
-        } else {
-            Some(self.data[row * self.length + col])
+    perimeter
+}
+
+/// Number every plot's region, in the order the regions are first met reading the map
+fn regions(grid: &Grid<u8>) -> Grid<usize> {
+    let mut labels = Grid::filled(grid.num_rows(), grid.num_cols(), usize::MAX);
+    let mut count = 0;
+    for (position, &plant) in grid.iter() {
+        if labels[position] != usize::MAX {
+            continue;
+        }
+        let mut visited = HashSet::new();
+        dfs(grid, plant, position, &mut visited);
+        for plot in visited {
+            labels[plot] = count;
         }
+        count += 1;
     }
+    labels
 }
 
-pub fn solve(input: &str) -> Option<(u64, u64)> {
-    let mut vec = Vec::new();
-    let mut length = 0;
-    for line in input.lines() {
-        for b in line.as_bytes() {
-            vec.push(*b);
-        }
-        length += 1;
+fn parse(input: &str) -> Result<Grid<u8>> {
+    let grid = Grid::parse(input, |_, c| {
+        u8::try_from(c).map_err(|_| Error::unexpected_char(c))
+    })?;
+    Ok(grid)
+}
+
+pub fn part1(input: &str) -> Result<u64> {
+    Ok(score(&parse(input)?, false))
+}
+
+pub fn part2(input: &str) -> Result<u64> {
+    Ok(score(&parse(input)?, true))
+}
+
+pub fn solve(input: &str) -> Result<(u64, u64)> {
+    let grid = parse(input)?;
+    Ok((score(&grid, false), score(&grid, true)))
+}
+
+/// Picture the garden with each region in its own color
+pub fn image(input: &str) -> Result<Image> {
+    let regions = regions(&parse(input)?);
+    Ok(Image::new(&regions, |&region| Rgb::label(region)))
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    fn day(&self) -> u8 {
+        12
+    }
+    fn title(&self) -> &'static str {
+        "Garden Groups"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |grid| score(&grid, false),
+            |grid| score(&grid, true),
+        )
+    }
+    fn picture(&self) -> Option<Picture> {
+        Some(image)
     }
-    assert_eq!(length * length, vec.len()); // Square grid;
-    let grid = SquareGrid::new(vec, length);
-    Some(grid.score())
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p12.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+RRRRIICCFF
+RRRRIICCCF
+VVRRRCCFFF
+VVRCCCJFFF
+VVVVCJJCFE
+VVIVCCJJEE
+VVIIICJJEE
+MIIIIIJJEE
+MIIISIJEEE
+MMMISSJEEE
+";
+    const EXAMPLE_WIDE: &str = "\
+RRRRIICCFFZ
+RRRRIICCCFZ
+VVRRRCCFFFZ
+VVRCCCJFFFZ
+VVVVCJJCFEZ
+VVIVCCJJEEZ
+VVIIICJJEEZ
+MIIIIIJJEEZ
+MIIISIJEEEZ
+MMMISSJEEEZ
+";
+    const EXAMPLE_E: &str = "\
+EEEEE
+EXXXX
+EEEEE
+EXXXX
+EEEEE
+";
+    const EXAMPLE_AB: &str = "\
+AAAAAA
+AAABBA
+AAABBA
+ABBAAA
+ABBAAA
+AAAAAA
+";
+
+    #[test]
+    fn day12_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 1930);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 1206);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (1930, 1206));
+    }
+
+    #[test]
+    fn day12_rectangular() {
+        // The extra column is one more region, 10 x 22 by perimeter and 10 x 4 by sides
+        assert_eq!(super::solve(EXAMPLE_WIDE).unwrap(), (2150, 1246));
+    }
+
+    #[test]
+    fn day12_example_sides() {
+        assert_eq!(super::part2(EXAMPLE_E).unwrap(), 236);
+        assert_eq!(super::part2(EXAMPLE_AB).unwrap(), 368);
+    }
+
     #[test]
     fn day12_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(12) else {
+            return;
+        };
+        crate::answers::assert_recorded(12, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day12_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,28 +1,35 @@
-use itertools::Itertools;
-use num_rational::Rational64;
-use regex::Regex;
+use crate::{answer, answers, parse, Answer, Error, Result, Solution, Timings};
+use num_rational::Ratio;
 
-const PART2_OFFSET: i64 = 10_000_000_000_000;
+const PART2_OFFSET: i128 = 10_000_000_000_000;
 
+/// Coordinates are parsed as 32-bit, so working in 128 bits nothing can overflow
+#[derive(Clone, Copy)]
 struct Equation {
-    a1: i64,
-    b1: i64,
-    a2: i64,
-    b2: i64,
-    eqs1: i64,
-    eqs2: i64,
+    a1: i128,
+    b1: i128,
+    a2: i128,
+    b2: i128,
+    eqs1: i128,
+    eqs2: i128,
 }
 
 impl Equation {
-    fn solve_tokens(&self) -> Option<i64> {
+    fn solve_tokens(&self) -> Option<i128> {
         // Solve a system of 2 equations
-        let elim_a = Rational64::new(-1 * self.a1, self.a2);
+        if self.a1 == 0 || self.a2 == 0 {
+            return None; // Cannot eliminate a
+        }
+        let elim_a = Ratio::new(-self.a1, self.a2);
         let bs = elim_a * self.b2 + self.b1;
         let eqs = elim_a * self.eqs2 + self.eqs1;
+        if bs == 0.into() {
+            return None; // The buttons move the claw along the same line
+        }
         let b_sol = eqs / bs;
         if b_sol.is_integer() {
             // Working, now plug into equation 1
-            let a_sol = (b_sol * (-1 * self.b1) + self.eqs1) / self.a1;
+            let a_sol = (b_sol * -self.b1 + self.eqs1) / self.a1;
             if a_sol.is_integer() {
                 // 3a + b
                 Some(a_sol.numer() * 3 + b_sol.numer())
@@ -35,59 +42,139 @@ impl Equation {
     }
 }
 
-pub fn get_ints(re: &Regex, line: &str) -> Option<(i64, i64)> {
-    let captures = re.captures(line)?;
-    captures
+fn parse(input: &str) -> Result<Vec<Equation>> {
+    let ints = |(line_idx, line): (usize, &str)| {
+        parse::fixed_uints::<u32, 2>(line_idx, line).map(|ints| ints.map(i128::from))
+    };
+    parse::sections(input)
+        .into_iter()
+        .map(|section| {
+            let &[button_a, button_b, prize] = section.as_slice() else {
+                let message = "expected two buttons and a prize";
+                return Err(Error::parse(message).at_line(section[0].0));
+            };
+            let [a1, a2] = ints(button_a)?;
+            let [b1, b2] = ints(button_b)?;
+            let [eqs1, eqs2] = ints(prize)?;
+            Ok(Equation {
+                a1,
+                a2,
+                b1,
+                b2,
+                eqs1,
+                eqs2,
+            })
+        })
+        .collect()
+}
+
+fn total_tokens(equations: &[Equation], offset: i128) -> i128 {
+    equations
         .iter()
-        .skip(1) // 0th is the full string match
-        .filter_map(|x| x.map(|x| x.as_str().parse::<i64>().ok()))
-        .flatten()
-        .collect_tuple()
+        .filter_map(|equation| {
+            Equation {
+                eqs1: equation.eqs1 + offset,
+                eqs2: equation.eqs2 + offset,
+                ..*equation
+            }
+            .solve_tokens()
+        })
+        .sum()
 }
 
-pub fn solve(input: &str) -> Option<(i64, i64)> {
-    // No capture non-numeric, capture number--repeated twice
-    let re = Regex::new(r"(?:[^\d]+)(\d+)(?:[^\d]+)(\d+)").unwrap();
-    let mut part1 = 0;
-    let mut part2 = 0;
-    for lines in input
-        .lines()
-        .filter(|x| !x.is_empty())
-        .chunks(3)
-        .into_iter()
-    {
-        let (line1, line2, line3) = lines.collect_tuple()?;
-        let (a1, a2) = get_ints(&re, &line1)?;
-        let (b1, b2) = get_ints(&re, &line2)?;
-        let (eqs1, eqs2) = get_ints(&re, &line3)?;
-        let equation = Equation {
-            a1,
-            a2,
-            b1,
-            b2,
-            eqs1,
-            eqs2,
-        };
-        if let Some(token_count) = equation.solve_tokens() {
-            part1 += token_count;
-        }
-        let equation2 = Equation {
-            eqs1: eqs1 + PART2_OFFSET,
-            eqs2: eqs2 + PART2_OFFSET,
-            ..equation
-        };
-        if let Some(token_count) = equation2.solve_tokens() {
-            part2 += token_count;
-        }
+pub fn part1(input: &str) -> Result<i128> {
+    Ok(total_tokens(&parse(input)?, 0))
+}
+
+pub fn part2(input: &str) -> Result<i128> {
+    Ok(total_tokens(&parse(input)?, PART2_OFFSET))
+}
+
+pub fn solve(input: &str) -> Result<(i128, i128)> {
+    let equations = parse(input)?;
+    Ok((
+        total_tokens(&equations, 0),
+        total_tokens(&equations, PART2_OFFSET),
+    ))
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    fn day(&self) -> u8 {
+        13
+    }
+    fn title(&self) -> &'static str {
+        "Claw Contraption"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |equations| total_tokens(&equations, 0),
+            |equations| total_tokens(&equations, PART2_OFFSET),
+        )
     }
-    Some((part1, part2))
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p13.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450
+
+Button A: X+69, Y+23
+Button B: X+27, Y+71
+Prize: X=18641, Y=10279
+";
+
+    #[test]
+    fn day13_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 480);
+        // No published answer for part 2, pinned to catch regressions
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 875318608908);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (480, 875318608908));
+    }
+
+    #[test]
+    fn day13_minus_is_not_a_sign() {
+        let machine = "Button A: X+-94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400\n";
+        assert_eq!(super::part1(machine).unwrap(), 280);
+    }
+
     #[test]
     fn day13_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(13) else {
+            return;
+        };
+        crate::answers::assert_recorded(13, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day13_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,89 +1,97 @@
-use itertools::Itertools;
+use crate::image::{Image, Picture, Rgb};
+use crate::visual::{Animate, Animation, Cell, Color, Frame};
+use crate::{answer, answers, parse, Answer, Direction, Error, Point, Result, Solution, Timings};
 use std::collections::HashSet;
 
-// const GRID_X_LENGTH: i64 = 11;
-// const GRID_Y_LENGTH: i64 = 7;
 const GRID_X_LENGTH: i64 = 101;
 const GRID_Y_LENGTH: i64 = 103;
+/// The room as `(num_rows, num_cols)`, for converting robot positions to grid positions
+const ROOM: (usize, usize) = (GRID_Y_LENGTH as usize, GRID_X_LENGTH as usize);
 
-fn print_tree(vec: Vec<[bool; GRID_X_LENGTH as usize]>) {
-    for arr in vec {
-        for a in arr {
-            if a {
-                print!("*")
-            } else {
-                print!(".")
-            }
+/// The room with every robot where it is after `seconds`
+fn room_frame(robots: &[Robot], seconds: i64) -> Frame {
+    let mut frame = Frame::filled(ROOM.0, ROOM.1, Cell::plain('.'));
+    for robot in robots {
+        let position = robot.position(seconds, GRID_X_LENGTH, GRID_Y_LENGTH);
+        if let Some(position) = position.to_grid(ROOM) {
+            frame.set(position, Cell::new('*', Color::Green));
         }
-        println!();
     }
+    frame
 }
 
+#[derive(Clone)]
 struct Robot {
-    px: i64,
-    py: i64,
-    vx: i64,
-    vy: i64,
+    position: Point,
+    velocity: Point,
+}
+
+impl Robot {
+    /// Position after the given number of seconds, wrapping around the edges of the room.
+    ///
+    /// Wrapping the velocity first gives the same position, and keeps any velocity from
+    /// overflowing
+    fn position(&self, seconds: i64, x_length: i64, y_length: i64) -> Point {
+        let velocity = self.velocity.wrap(x_length, y_length);
+        (self.position + velocity * seconds).wrap(x_length, y_length)
+    }
+}
+
+fn parse(input: &str) -> Result<Vec<Robot>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(line_idx, line)| {
+            let [px, py, vx, vy] = parse::fixed_ints(line_idx, line)?;
+            let position = Point::new(px, py);
+            if position.to_grid(ROOM).is_none() {
+                return Err(Error::parse("robot starts outside the room").at_line(line_idx));
+            }
+            Ok(Robot {
+                position,
+                velocity: Point::new(vx, vy),
+            })
+        })
+        .collect()
 }
 
-pub fn solve(input: &str) -> Option<(u64, u64)> {
+fn safety_factor(robots: &[Robot], x_length: i64, y_length: i64) -> u64 {
     const PART1_SECONDS: i64 = 100;
-    let mut part1 = [0, 0, 0, 0];
+    let mut quadrants = [0, 0, 0, 0];
+    for robot in robots.iter() {
+        let Point { x: x_pos, y: y_pos } = robot.position(PART1_SECONDS, x_length, y_length);
+        if x_pos > x_length / 2 {
+            if y_pos > y_length / 2 {
+                quadrants[0] += 1;
+            } else if y_pos < y_length / 2 {
+                quadrants[1] += 1;
+            }
+        } else if x_pos < x_length / 2 {
+            if y_pos > y_length / 2 {
+                quadrants[2] += 1;
+            } else if y_pos < y_length / 2 {
+                quadrants[3] += 1;
+            }
+        }
+    }
+    quadrants.into_iter().product()
+}
+
+/// Look for the second where the robots form one anomalously large clump, i.e. the tree
+fn tree_seconds(robots: &[Robot]) -> i64 {
     let mut max_score = 0;
     let mut part2 = 0;
     let mut score_buffer = [0; 10];
-    let mut best_tree = Vec::new();
-    let robots: Vec<_> = input
-        .lines()
-        .map(|line| {
-            let (px, py, vx, vy) = line
-                .split(['=', ',', ' '])
-                .filter_map(|x| x.parse::<i64>().ok())
-                .collect_tuple()
-                .unwrap();
-            Robot { px, py, vx, vy }
-        })
-        .collect();
     for seconds in 0..10000 {
         let mut points = HashSet::new();
         for robot in robots.iter() {
-            // Set final position
-            let mut x_pos = (robot.px + robot.vx * seconds) % GRID_X_LENGTH;
-            let mut y_pos = (robot.py + robot.vy * seconds) % GRID_Y_LENGTH;
-            if x_pos < 0 {
-                x_pos = GRID_X_LENGTH + x_pos;
-            }
-            if y_pos < 0 {
-                y_pos = GRID_Y_LENGTH + y_pos;
-            }
-            // Set part 1
-            if seconds == PART1_SECONDS {
-                if x_pos > GRID_X_LENGTH / 2 {
-                    if y_pos > GRID_Y_LENGTH / 2 {
-                        part1[0] += 1;
-                    } else if y_pos < GRID_Y_LENGTH / 2 {
-                        part1[1] += 1;
-                    }
-                } else if x_pos < GRID_X_LENGTH / 2 {
-                    if y_pos > GRID_Y_LENGTH / 2 {
-                        part1[2] += 1;
-                    } else if y_pos < GRID_Y_LENGTH / 2 {
-                        part1[3] += 1;
-                    }
-                }
-            }
-            points.insert((y_pos as usize, x_pos as usize));
+            let position = robot.position(seconds, GRID_X_LENGTH, GRID_Y_LENGTH);
+            points.insert(position.to_grid(ROOM).expect("robots wrap into the room"));
         }
         let grid = Grid { data: points };
         let grid_score = grid.get_largest_comp();
         if grid_score > max_score {
-            // I'm not positive if this indexing is right, but it works
-            let mut data = vec![[false; GRID_X_LENGTH as usize]; GRID_Y_LENGTH as usize];
-            for (y_pos, x_pos) in grid.data {
-                data[y_pos][x_pos] = true;
-            }
             max_score = grid_score;
-            best_tree = data;
             part2 = seconds;
             // Early stop if maximum clump size is extremely anomalous
             let dev = std_smooth(&score_buffer);
@@ -93,8 +101,26 @@ pub fn solve(input: &str) -> Option<(u64, u64)> {
         }
         score_buffer[seconds as usize % score_buffer.len()] = grid_score;
     }
-    print_tree(best_tree);
-    Some((part1.into_iter().product(), part2 as u64))
+    part2
+}
+
+pub fn part1(input: &str) -> Result<u64> {
+    Ok(safety_factor(&parse(input)?, GRID_X_LENGTH, GRID_Y_LENGTH))
+}
+
+pub fn part2(input: &str) -> Result<u64> {
+    let seconds = tree_seconds(&parse(input)?);
+    Ok(u64::try_from(seconds).expect("the search counts up from 0"))
+}
+
+pub fn solve(input: &str) -> Result<(u64, u64)> {
+    let robots = parse(input)?;
+    let part1 = safety_factor(&robots, GRID_X_LENGTH, GRID_Y_LENGTH);
+    let seconds = tree_seconds(&robots);
+    Ok((
+        part1,
+        u64::try_from(seconds).expect("the search counts up from 0"),
+    ))
 }
 
 /// Take the standard deviation of recent measurements, but add an offset so it is never too low
@@ -135,19 +161,12 @@ impl Grid {
     }
     fn dfs(&self, position: (usize, usize), visited: &mut HashSet<(usize, usize)>) {
         visited.insert(position);
-        let (row, col) = position;
-        let neighbors = [
-            (row - 1, col),
-            (row, col + 1),
-            (row + 1, col),
-            (row, col - 1),
-        ];
-        for n in neighbors {
-            let (row, col) = n;
-            if self.get(row, col) {
-                if !visited.contains(&(row, col)) {
-                    self.dfs(n, visited);
-                }
+        for direction in Direction::CARDINAL {
+            let Some((row, col)) = direction.step(position, ROOM) else {
+                continue;
+            };
+            if self.get(row, col) && !visited.contains(&(row, col)) {
+                self.dfs((row, col), visited);
             }
         }
     }
@@ -156,11 +175,114 @@ impl Grid {
     }
 }
 
+/// Play the robots moving, one second per frame, until they form the tree
+pub fn animate(input: &str, animation: &mut Animation) -> Result<()> {
+    let robots = parse(input)?;
+    let tree = tree_seconds(&robots);
+    for seconds in 0..tree {
+        animation.show(|| room_frame(&robots, seconds).caption(format!("{} seconds", seconds)));
+    }
+    let frame = room_frame(&robots, tree).caption(format!("{} seconds, the tree", tree));
+    animation.show_always(frame);
+    Ok(())
+}
+
+/// Picture the robots at the second they form the tree
+pub fn image(input: &str) -> Result<Image> {
+    let robots = parse(input)?;
+    let seconds = tree_seconds(&robots);
+    let mut image = Image::filled(ROOM.0, ROOM.1, Rgb::BLACK);
+    for robot in &robots {
+        let position = robot.position(seconds, GRID_X_LENGTH, GRID_Y_LENGTH);
+        if let Some(position) = position.to_grid(ROOM) {
+            image.set(position, Rgb::GREEN);
+        }
+    }
+    Ok(image)
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    fn day(&self) -> u8 {
+        14
+    }
+    fn title(&self) -> &'static str {
+        "Restroom Redoubt"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |robots| safety_factor(&robots, GRID_X_LENGTH, GRID_Y_LENGTH),
+            |robots| tree_seconds(&robots),
+        )
+    }
+    fn animate(&self) -> Option<Animate> {
+        Some(animate)
+    }
+    fn picture(&self) -> Option<Picture> {
+        Some(image)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p14.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+p=0,4 v=3,-3
+p=6,3 v=-1,-3
+p=10,3 v=-1,2
+p=2,0 v=2,-1
+p=0,0 v=1,3
+p=3,0 v=-2,-2
+p=7,6 v=-1,-3
+p=3,0 v=-1,-2
+p=9,3 v=2,3
+p=7,3 v=-1,2
+p=2,4 v=2,-3
+p=9,5 v=-3,-3
+";
+
+    #[test]
+    fn day14_example() {
+        // The example room is only 11 wide and 7 tall, and part 2 has no example
+        let robots = super::parse(EXAMPLE).unwrap();
+        assert_eq!(super::safety_factor(&robots, 11, 7), 12);
+    }
+
+    #[test]
+    fn day14_fast_robot() {
+        let input = "p=0,0 v=9223372036854775807,1\n";
+        let robots = super::parse(input).unwrap();
+        let expected = crate::Point::new(i64::MAX % 101 * 100 % 101, 100);
+        assert_eq!(robots[0].position(100, 101, 103), expected);
+        assert_eq!(super::part1(input).unwrap(), 0);
+    }
+
     #[test]
     fn day14_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(14) else {
+            return;
+        };
+        crate::answers::assert_recorded(14, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day14_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
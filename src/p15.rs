@@ -1,4 +1,5 @@
-use super::Direction;
+use super::visual::{Animate, Animation, Cell, Color, Frame};
+use super::{answer, answers, Answer, Direction, Error, Grid, Result, Solution, Timings};
 use itertools::Itertools;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -19,48 +20,64 @@ impl Tile {
             Direction::West
         }
     }
+    fn symbol(&self) -> char {
+        match self {
+            Tile::Robot => '@',
+            Tile::Wall => '#',
+            Tile::Box => 'O',
+            Tile::Empty => '.',
+            Tile::BoxLeft => '[',
+            Tile::BoxRight => ']',
+        }
+    }
+}
+
+impl std::fmt::Display for Tile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
 }
 
-struct Grid {
-    data: Vec<Tile>,
-    num_rows: usize,
-    num_cols: usize,
+#[derive(Clone)]
+struct Warehouse {
+    map: Grid<Tile>,
     robot_row: usize,
     robot_col: usize,
 }
-impl Grid {
-    fn new(data: Vec<Tile>, num_rows: usize) -> Self {
-        let num_cols = data.len() / num_rows;
-        let mut out = Self {
-            data,
-            num_rows,
-            num_cols,
-            robot_row: 0,
-            robot_col: 0,
-        };
-        let (robot_row, robot_col) = out.find_robot();
-        out.robot_row = robot_row;
-        out.robot_col = robot_col;
-        out
-    }
-    fn find_robot(&self) -> (usize, usize) {
-        for row in 0..self.num_rows {
-            for col in 0..self.num_cols {
-                if let Tile::Robot = self.get(row, col) {
-                    return (row, col);
-                }
+impl Warehouse {
+    fn new(map: Grid<Tile>) -> Result<Self> {
+        let (robot_row, robot_col) = map
+            .find(&Tile::Robot)
+            .ok_or_else(|| Error::parse("no robot in the warehouse"))?;
+        Ok(Self {
+            map,
+            robot_row,
+            robot_col,
+        })
+    }
+    /// Double the width of everything except the robot for part 2
+    fn widen(&self) -> Result<Self> {
+        let mut data = Vec::with_capacity(2 * self.map.len());
+        for row in self.map.rows() {
+            for tile in row {
+                let (left, right) = match tile {
+                    Tile::Robot => (Tile::Robot, Tile::Empty),
+                    Tile::Box => (Tile::BoxLeft, Tile::BoxRight),
+                    _ => (*tile, *tile),
+                };
+                data.push(left);
+                data.push(right);
             }
         }
-        unimplemented!()
+        let num_cols = 2 * self.map.num_cols();
+        Self::new(Grid::new(data, self.map.num_rows(), num_cols))
     }
     fn score_boxes(&self) -> u64 {
         let mut out = 0;
-        for row in 0..self.num_rows {
-            for col in 0..self.num_cols {
-                // The left side of the box is always closer to the left side, and equally close to the top
-                if let Tile::Box | Tile::BoxLeft = self.get(row, col) {
-                    out += 100 * row + col;
-                }
+        for ((row, col), tile) in self.map.iter() {
+            // The left side of the box is always closer to the left side, and equally close to the top
+            if let Tile::Box | Tile::BoxLeft = tile {
+                out += 100 * row + col;
             }
         }
         out as u64
@@ -77,8 +94,8 @@ impl Grid {
     fn can_push(&self, row: usize, col: usize, dir: Direction) -> bool {
         let (next_row, next_col) = dir.get_square((row, col));
         let next_tile = self.get(next_row, next_col);
-        let can_push = match next_tile {
-            Tile::Robot => unimplemented!(),
+        match next_tile {
+            Tile::Robot => unreachable!("parsing rejects a second robot"),
             Tile::Wall => false,
             Tile::Box => self.can_push(next_row, next_col, dir),
             Tile::Empty => true,
@@ -91,14 +108,12 @@ impl Grid {
                         let (neighbor_row, neighbor_col) =
                             neighbor_direction.get_square((next_row, next_col));
                         // And make sure both halves are pushable
-                        let pushable = self.can_push(next_row, next_col, dir)
-                            && self.can_push(neighbor_row, neighbor_col, dir);
-                        pushable
+                        self.can_push(next_row, next_col, dir)
+                            && self.can_push(neighbor_row, neighbor_col, dir)
                     }
                 }
             }
-        };
-        can_push
+        }
     }
     /// Recursively executes a push if it is valid
     fn push(&mut self, row: usize, col: usize, dir: Direction) -> bool {
@@ -106,7 +121,7 @@ impl Grid {
         let (next_row, next_col) = dir.get_square((row, col));
         let next_tile = self.get(next_row, next_col);
         let can_push = match next_tile {
-            Tile::Robot => unimplemented!(),
+            Tile::Robot => unreachable!("parsing rejects a second robot"),
             Tile::Wall => false,
             Tile::Box => self.push(next_row, next_col, dir),
             Tile::Empty => true,
@@ -136,84 +151,202 @@ impl Grid {
         }
         can_push
     }
+    fn frame(&self, caption: String) -> Frame {
+        Frame::new(&self.map, |tile| {
+            let color = match tile {
+                Tile::Robot => Color::Red,
+                Tile::Wall => Color::Gray,
+                Tile::Box | Tile::BoxLeft | Tile::BoxRight => Color::Yellow,
+                Tile::Empty => Color::Plain,
+            };
+            Cell::new(tile.symbol(), color)
+        })
+        .caption(caption)
+    }
     fn get_mut(&mut self, row: usize, col: usize) -> &mut Tile {
-        &mut self.data[row * self.num_cols + col]
+        &mut self.map[(row, col)]
     }
     fn get(&self, row: usize, col: usize) -> Tile {
-        self.data[row * self.num_cols + col]
+        self.map[(row, col)]
     }
 }
 
-impl std::fmt::Debug for Grid {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f)?;
-        for row in 0..self.num_rows {
-            for col in 0..self.num_cols {
-                let s = match self.get(row, col) {
-                    Tile::Robot => "@",
-                    Tile::Wall => "#",
-                    Tile::Box => "O",
-                    Tile::Empty => ".",
-                    Tile::BoxLeft => "[",
-                    Tile::BoxRight => "]",
-                };
-                write!(f, "{}", s)?;
+fn parse(input: &str) -> Result<(Warehouse, Vec<Direction>)> {
+    let mut has_robot = false;
+    let mut lines = input.lines().enumerate();
+    let map = Grid::parse_lines(
+        lines.take_while_ref(|(_, line)| !line.is_empty()),
+        |_, c| match c {
+            '.' => Ok(Tile::Empty),
+            '@' if has_robot => Err(Error::parse("found a second robot")),
+            '@' => {
+                has_robot = true;
+                Ok(Tile::Robot)
             }
-            writeln!(f)?;
+            '#' => Ok(Tile::Wall),
+            'O' => Ok(Tile::Box),
+            _ => Err(Error::unexpected_char(c)),
+        },
+    )?;
+    // The robot only stays in bounds if walls surround the warehouse
+    let enclosed = map
+        .iter()
+        .all(|(position, tile)| !map.on_edge(position) || *tile == Tile::Wall);
+    if !enclosed {
+        return Err(Error::parse("the warehouse is not surrounded by walls"));
+    }
+    let mut commands = Vec::new();
+    for (row, line) in lines {
+        for (col, command) in line.chars().enumerate() {
+            let command = Direction::try_from(command).map_err(|err| err.at(row, col))?;
+            commands.push(command);
         }
-        writeln!(f)
     }
+    Ok((Warehouse::new(map)?, commands))
 }
 
-pub fn solve(input: &str) -> Option<(u64, u64)> {
-    let mut data1 = Vec::new();
-    let mut data2 = Vec::new();
-    let mut num_lines = 0;
-    let mut lines = input.lines();
-    for line in lines.take_while_ref(|line| !line.is_empty()) {
-        for (tile1, tile2, tile3) in line.chars().map(|c| match c {
-            '.' => (Tile::Empty, Tile::Empty, Tile::Empty),
-            '@' => (Tile::Robot, Tile::Robot, Tile::Empty),
-            '#' => (Tile::Wall, Tile::Wall, Tile::Wall),
-            'O' => (Tile::Box, Tile::BoxLeft, Tile::BoxRight),
-            _ => unimplemented!(),
-        }) {
-            // Part 1 Parse
-            data1.push(tile1);
-            // Part 2 Parse
-            data2.push(tile2);
-            data2.push(tile3);
-        }
-        num_lines += 1;
+fn run(mut warehouse: Warehouse, commands: &[Direction]) -> u64 {
+    for command in commands.iter().copied() {
+        warehouse.robot_move(command);
     }
-    let mut commands = Vec::new();
-    for line in lines {
-        for command in line.chars() {
-            let command = match command {
-                '^' => Direction::North,
-                '>' => Direction::East,
-                'v' => Direction::South,
-                '<' => Direction::West,
-                _ => return None,
-            };
-            commands.push(command);
+    warehouse.score_boxes()
+}
+
+pub fn part1(input: &str) -> Result<u64> {
+    let (warehouse, commands) = parse(input)?;
+    Ok(run(warehouse, &commands))
+}
+
+pub fn part2(input: &str) -> Result<u64> {
+    let (warehouse, commands) = parse(input)?;
+    Ok(run(warehouse.widen()?, &commands))
+}
+
+pub fn solve(input: &str) -> Result<(u64, u64)> {
+    let (warehouse, commands) = parse(input)?;
+    let wide = warehouse.widen()?;
+    Ok((run(warehouse, &commands), run(wide, &commands)))
+}
+
+/// Play the robot's moves, one per frame, in the warehouse of part 1 and then the wide one
+pub fn animate(input: &str, animation: &mut Animation) -> Result<()> {
+    let (warehouse, commands) = parse(input)?;
+    let wide = warehouse.widen()?;
+    for (part, mut warehouse) in [(1, warehouse), (2, wide)] {
+        for (idx, command) in commands.iter().enumerate() {
+            warehouse.robot_move(*command);
+            let caption = || format!("part {}, move {} of {}", part, idx + 1, commands.len());
+            animation.show(|| warehouse.frame(caption()));
         }
+        let caption = format!(
+            "part {}, GPS coordinates sum to {}",
+            part,
+            warehouse.score_boxes()
+        );
+        animation.show_always(warehouse.frame(caption));
     }
+    Ok(())
+}
 
-    let mut grid = Grid::new(data1, num_lines);
-    let mut grid2 = Grid::new(data2, num_lines);
-    for command in commands {
-        grid.robot_move(command);
-        grid2.robot_move(command);
+pub struct Day15;
+
+impl Solution for Day15 {
+    fn day(&self) -> u8 {
+        15
+    }
+    fn title(&self) -> &'static str {
+        "Warehouse Woes"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |(warehouse, commands)| run(warehouse, &commands),
+            |(warehouse, commands)| warehouse.widen().map(|wide| run(wide, &commands)),
+        )
+    }
+    fn animate(&self) -> Option<Animate> {
+        Some(animate)
     }
-    Some((grid.score_boxes(), grid2.score_boxes()))
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p15.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+##########
+#..O..O.O#
+#......O.#
+#.OO..O.O#
+#..O@..O.#
+#O#..O...#
+#O..O..O.#
+#.OO.O.OO#
+#....O...#
+##########
+
+<vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^
+vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
+><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<
+<<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^
+^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><
+^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^
+>^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^
+<><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>
+^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
+v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^
+";
+    const EXAMPLE_SMALL: &str = "\
+########
+#..O.O.#
+##@.O..#
+#...O..#
+#.#.O..#
+#...O..#
+#......#
+########
+
+<^^>>>vv<v>>v<<
+";
+
+    #[test]
+    fn day15_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 10092);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 9021);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (10092, 9021));
+        assert_eq!(super::part1(EXAMPLE_SMALL).unwrap(), 2028);
+    }
+
+    #[test]
+    fn day15_letters_are_not_moves() {
+        let input = EXAMPLE_SMALL.replace("<^^>", "<^N>");
+        let err = super::part1(&input).unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(10), Some(3)));
+    }
+
     #[test]
     fn day15_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(15) else {
+            return;
+        };
+        crate::answers::assert_recorded(15, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day15_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,5 +1,8 @@
-use super::Direction;
-use std::collections::{BinaryHeap, HashSet};
+use super::image::{Image, Picture, Rgb};
+use super::search::{self, Search};
+use super::visual::{self, Animate, Animation, Cell, Color, Frame};
+use super::{answer, answers, parse, Answer, Direction, Error, Grid, Result, Solution, Timings};
+use std::collections::HashSet;
 
 #[derive(Debug, Clone, Copy)]
 enum Tile {
@@ -8,7 +11,8 @@ enum Tile {
     Goal,
 }
 
-#[derive(PartialEq, Clone, Copy, Eq, PartialOrd, Ord, Debug)]
+/// A tile of the maze and the way the reindeer faces on it
+#[derive(PartialEq, Clone, Copy, Eq, Hash, Debug)]
 struct Position {
     row: usize,
     col: usize,
@@ -31,196 +35,245 @@ impl Position {
         Self::new(self.row, self.col, self.direction.turn())
     }
     fn counter_rotate(&self) -> Self {
-        Self::new(self.row, self.col, self.direction.turn().turn().turn())
+        Self::new(self.row, self.col, self.direction.turn_left())
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct State {
-    cost: usize,
-    position: Position,
+#[derive(Clone)]
+struct Maze {
+    map: Grid<Tile>,
+    start_row: usize,
+    start_col: usize,
 }
-
-// From https://doc.rust-lang.org/nightly/std/collections/binary_heap/index.html#examples
-
-// The priority queue depends on `Ord`.
-// Explicitly implement the trait so the queue becomes a min-heap
-// instead of a max-heap.
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other
-            .cost
-            .cmp(&self.cost)
-            .then_with(|| self.position.cmp(&other.position))
+impl Maze {
+    /// Lowest score to the end, along with the search to recover every best path
+    fn shortest_path(&self) -> Option<(u64, Search<Position>)> {
+        let start = Position::new(self.start_row, self.start_col, Direction::East);
+        let moves = |position: Position| {
+            [
+                (position.forward(), 1),
+                (position.rotate(), 1000),
+                (position.counter_rotate(), 1000),
+            ]
+            .into_iter()
+            .filter(|(next, _)| !matches!(self.get(next.row, next.col), Tile::Wall))
+        };
+        let is_end =
+            |position: Position| matches!(self.get(position.row, position.col), Tile::Goal);
+        let search = search::dijkstra(start, moves, is_end);
+        let cost = search.distance(search.goal()?)?;
+        Some((cost, search))
+    }
+    /// Count the tiles on any of the best paths, whichever way they are crossed
+    fn best_tiles(&self, search: &Search<Position>) -> u64 {
+        let Some(end) = search.goal() else {
+            return 0;
+        };
+        let tiles: HashSet<_> = search
+            .on_shortest_paths(end)
+            .into_iter()
+            .map(|p| (p.row, p.col))
+            .collect();
+        tiles.len() as u64
+    }
+    fn frame(&self) -> Frame {
+        let mut frame = Frame::new(&self.map, |tile| match tile {
+            Tile::Empty => Cell::plain('.'),
+            Tile::Wall => Cell::new('#', Color::Gray),
+            Tile::Goal => Cell::new('E', Color::Red),
+        });
+        frame.set((self.start_row, self.start_col), Cell::new('S', Color::Red));
+        frame
+    }
+    fn get(&self, row: usize, col: usize) -> Tile {
+        self.map[(row, col)]
     }
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+fn parse(input: &str) -> Result<Maze> {
+    let tile = |c| match c {
+        '#' => Ok(Tile::Wall),
+        '.' | 'S' => Ok(Tile::Empty),
+        'E' => Ok(Tile::Goal),
+        _ => Err(Error::unexpected_char(c)),
+    };
+    let (map, [(start_row, start_col), _]) =
+        parse::grid_with_markers(input.lines().enumerate(), ['S', 'E'], tile)?;
+    // The search never bounds checks, so it relies on the maze being walled in
+    let enclosed = map
+        .iter()
+        .all(|(position, tile)| !map.on_edge(position) || matches!(tile, Tile::Wall));
+    if !enclosed {
+        return Err(Error::parse("the maze is not surrounded by walls"));
     }
+    Ok(Maze {
+        map,
+        start_row,
+        start_col,
+    })
 }
 
-struct Edge {
-    node: Position,
-    cost: usize,
+fn no_path() -> Error {
+    Error::no_solution("the end cannot be reached")
 }
 
-impl Edge {
-    fn new(node: Position, cost: usize) -> Self {
-        Self { node, cost }
-    }
+pub fn part1(input: &str) -> Result<u64> {
+    Ok(parse(input)?.shortest_path().ok_or_else(no_path)?.0)
 }
 
-/// Fixed size history buffer
-#[derive(Clone, Debug)]
-struct History {
-    data: [Position; 4],
-    len: usize,
+pub fn part2(input: &str) -> Result<u64> {
+    let maze = parse(input)?;
+    let (_, search) = maze.shortest_path().ok_or_else(no_path)?;
+    Ok(maze.best_tiles(&search))
 }
 
-impl History {
-    fn new() -> Self {
-        History {
-            data: [Position::new(0, 0, Direction::North); 4],
-            len: 0,
+pub fn solve(input: &str) -> Result<(u64, u64)> {
+    let maze = parse(input)?;
+    let (cost, search) = maze.shortest_path().ok_or_else(no_path)?;
+    Ok((cost, maze.best_tiles(&search)))
+}
+
+/// Play the search spreading out from the start, one score per frame, then show the best paths
+pub fn animate(input: &str, animation: &mut Animation) -> Result<()> {
+    let maze = parse(input)?;
+    let (cost, search) = maze.shortest_path().ok_or_else(no_path)?;
+    let mut frame = maze.frame();
+    for (score, tiles) in visual::waves(&search, |p| (p.row, p.col)) {
+        for tile in tiles {
+            frame.set(tile, Cell::new('o', Color::Cyan));
         }
+        animation.show(|| frame.clone().caption(format!("score {}", score)));
     }
-    fn push(&mut self, p: Position) {
-        self.data[self.len] = p;
-        self.len += 1;
-    }
-    fn clear(&mut self) {
-        self.len = 0;
-    }
-    fn access(&self) -> &[Position] {
-        &self.data[0..self.len]
+    let best = search.on_shortest_paths(search.goal().ok_or_else(no_path)?);
+    for position in best {
+        frame.set((position.row, position.col), Cell::new('O', Color::Green));
     }
+    let caption = format!(
+        "best score {}, {} tiles on a best path",
+        cost,
+        maze.best_tiles(&search)
+    );
+    animation.show_always(frame.caption(caption));
+    Ok(())
 }
 
-struct Grid {
-    data: Vec<Tile>,
-    num_cols: usize,
-    start_row: usize,
-    start_col: usize,
+/// Picture the maze with the tiles on a best path
+pub fn image(input: &str) -> Result<Image> {
+    let maze = parse(input)?;
+    let (_, search) = maze.shortest_path().ok_or_else(no_path)?;
+    let mut image = Image::new(&maze.map, |tile| match tile {
+        Tile::Empty => Rgb::WHITE,
+        Tile::Wall => Rgb::GRAY,
+        Tile::Goal => Rgb::RED,
+    });
+    let goal = search.goal().ok_or_else(no_path)?;
+    for position in search.on_shortest_paths(goal) {
+        image.set((position.row, position.col), Rgb::GREEN);
+    }
+    image.set((maze.start_row, maze.start_col), Rgb::RED);
+    image.set((goal.row, goal.col), Rgb::RED);
+    Ok(image)
 }
-impl Grid {
-    fn new(data: Vec<Tile>, num_rows: usize, start_row: usize, start_col: usize) -> Self {
-        let num_cols = data.len() / num_rows;
-        Self {
-            data,
-            num_cols,
-            start_row,
-            start_col,
-        }
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    fn day(&self) -> u8 {
+        16
     }
-    /// Recursively walk all found paths back to the start
-    fn visit(&self, idx: usize, history: &[History], visited: &mut HashSet<(usize, usize)>) {
-        for p in history[idx].access().iter() {
-            visited.insert((p.row, p.col));
-            let next = self.get_idx(*p);
-            self.visit(next, history, visited);
-        }
+    fn title(&self) -> &'static str {
+        "Reindeer Maze"
     }
-    fn shortest_path(&self) -> Option<(u64, u64)> {
-        let mut prev = vec![History::new(); 4 * self.data.len()];
-        let mut dist = vec![usize::MAX; 4 * self.data.len()];
-
-        let start_node = Position::new(self.start_row, self.start_col, Direction::East);
-        let start = self.get_idx(start_node);
-        let mut heap = BinaryHeap::new();
-        dist[start] = 0;
-        heap.push(State {
-            cost: 0,
-            position: start_node,
-        });
-        // Examine the frontier with lower cost nodes first (min-heap)
-        while let Some(State { cost, position }) = heap.pop() {
-            if let Tile::Goal = self.get(position.row, position.col) {
-                // Compute Part 2
-                let end_idx = self.get_idx(position);
-                let mut visited = HashSet::new();
-                visited.insert((position.row, position.col));
-                self.visit(end_idx, &prev, &mut visited);
-                return Some((cost as u64, visited.len() as u64));
-            }
-
-            // Bad node, look no further
-            if cost > dist[self.get_idx(position)] {
-                continue;
-            }
-            let adj_list = [
-                Edge::new(position.forward(), 1),
-                Edge::new(position.rotate(), 1000),
-                Edge::new(position.counter_rotate(), 1000),
-            ];
-            for edge in &adj_list {
-                if let Tile::Wall = self.get(edge.node.row, edge.node.col) {
-                    continue;
-                }
-                let next = State {
-                    cost: cost + edge.cost,
-                    position: edge.node,
-                };
-
-                let next_idx = self.get_idx(next.position);
-                if next.cost < dist[next_idx] {
-                    // Update
-                    heap.push(next);
-                    prev[next_idx].clear();
-                    prev[next_idx].push(position);
-                    dist[next_idx] = next.cost;
-                } else if next.cost == dist[next_idx] {
-                    // Do not need to add the node, but we need to note it for walking backward
-                    prev[next_idx].push(position);
-                }
-            }
-        }
-        None
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
     }
-    fn get_simple_idx(&self, row: usize, col: usize) -> usize {
-        row * self.num_cols + col
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
     }
-    fn get_idx(&self, p: Position) -> usize {
-        p.row * (4 * self.num_cols) + p.col * 4 + p.direction as usize
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
     }
-    fn get(&self, row: usize, col: usize) -> Tile {
-        self.data[self.get_simple_idx(row, col)]
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |maze| maze.shortest_path().map(|(cost, _)| cost),
+            |maze| {
+                maze.shortest_path()
+                    .map(|(_, search)| maze.best_tiles(&search))
+            },
+        )
     }
-}
-
-pub fn solve(input: &str) -> Option<(u64, u64)> {
-    let mut data = Vec::new();
-    let mut num_rows = 0;
-    let mut start_row = 0;
-    let mut start_col = 0;
-    for line in input.lines() {
-        for (idx, c) in line.chars().enumerate() {
-            let tile = match c {
-                '#' => Tile::Wall,
-                '.' => Tile::Empty,
-                'E' => Tile::Goal,
-                'S' => {
-                    start_row = num_rows;
-                    start_col = idx;
-                    Tile::Empty
-                }
-                _ => unimplemented!(),
-            };
-            data.push(tile);
-        }
-        num_rows += 1;
+    fn animate(&self) -> Option<Animate> {
+        Some(animate)
+    }
+    fn picture(&self) -> Option<Picture> {
+        Some(image)
     }
-    let grid = Grid::new(data, num_rows, start_row, start_col);
-    let (part1, part2) = grid.shortest_path()?;
-    Some((part1, part2))
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p16.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############
+";
+    const EXAMPLE_SECOND: &str = "\
+#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################
+";
+
+    #[test]
+    fn day16_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 7036);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 45);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (7036, 45));
+        assert_eq!(super::solve(EXAMPLE_SECOND).unwrap(), (11048, 64));
+    }
+
     #[test]
     fn day16_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(16) else {
+            return;
+        };
+        crate::answers::assert_recorded(16, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day16_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,7 +1,11 @@
-use std::fmt::Write;
-
+use crate::error::parse_token;
+use crate::{answer, answers, parse, Answer, Error, Result, Solution, Timings};
 use itertools::Itertools;
 
+/// Puzzle programs halt within a few hundred steps, so one still running after this many
+/// is taken to loop forever
+const MAX_STEPS: usize = 1_000_000;
+
 #[derive(Debug, PartialEq, Eq)]
 struct Operation {
     code: OpCode,
@@ -9,7 +13,7 @@ struct Operation {
 }
 
 impl Operation {
-    fn new(val1: u8, val2: u8) -> Self {
+    fn new(val1: u8, val2: u8) -> Option<Self> {
         use OpCode::*;
         let code = match val1 {
             0 => ADV,
@@ -20,12 +24,17 @@ impl Operation {
             5 => OUT,
             6 => BDV,
             7 => CDV,
-            _ => unimplemented!(),
+            _ => return None,
         };
-        Self { code, arg: val2 }
+        Some(Self { code, arg: val2 })
+    }
+    fn has_combo_arg(&self) -> bool {
+        use OpCode::*;
+        matches!(self.code, ADV | BST | OUT | BDV | CDV)
     }
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, PartialEq, Eq)]
 enum OpCode {
     ADV,
@@ -38,7 +47,7 @@ enum OpCode {
     CDV,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Machine {
     reg_a: u64,
     reg_b: u64,
@@ -65,21 +74,24 @@ impl Machine {
             4 => self.reg_a,
             5 => self.reg_b,
             6 => self.reg_c,
-            _ => unimplemented!(),
+            _ => unreachable!("parsing rejects combo operand 7"),
         }
     }
+    /// A divided by 2 to the power of the combo operand
+    fn divide(&self, val: u8) -> u64 {
+        u32::try_from(self.combo(val))
+            .ok()
+            .and_then(|shift| self.reg_a.checked_shr(shift))
+            .unwrap_or(0)
+    }
     /// Execute the program and return its string output for part 1
-    fn run(&mut self) -> Option<String> {
-        let mut out = String::new();
-        while self.step().is_some() {}
-        for i in self.out_buffer[0..self.out_buffer.len() - 1]
-            .iter()
-            .copied()
-        {
-            write!(&mut out, "{},", i).ok()?;
+    fn run(&mut self) -> Result<String> {
+        for _ in 0..MAX_STEPS {
+            if self.step().is_none() {
+                return Ok(self.out_buffer.iter().join(","));
+            }
         }
-        write!(&mut out, "{}", self.out_buffer.last()?).ok()?;
-        Some(out)
+        Err(Error::no_solution("the program does not halt"))
     }
     /// Look for the valid input with backtracking, and abort early if a solution cannot become valid
     fn recurse(&mut self, start_val: u8, high_bits: u64, num_steps: u8) -> Option<u64> {
@@ -99,44 +111,55 @@ impl Machine {
         }
         None
     }
-    fn search(&mut self) -> Option<u64> {
+    fn search(&mut self) -> Result<u64> {
         let blocks: Vec<_> = self
             .code
             .chunks_exact(2)
-            .map(|s| Operation::new(s[0], s[1]))
+            .filter_map(|s| Operation::new(s[0], s[1]))
             .collect();
+        let unsupported = |reason: &str| {
+            Err(Error::no_solution(format!(
+                "the search only supports programs that {}",
+                reason
+            )))
+        };
         // Check preconditions
         // Only jump is at the end of the program, and jumps on A != 0 to 0
-        let len = blocks.len();
-        assert_eq!(blocks.last(), Some(&Operation::new(3, 0)));
-        assert!(blocks
-            .iter()
-            .take(len - 1)
-            .find(|b| b.code == OpCode::JNZ)
-            .is_none());
+        let Some((last, body)) = blocks.split_last() else {
+            return unsupported("are not empty");
+        };
+        if *last != Operation::new(3, 0).unwrap() || body.iter().any(|b| b.code == OpCode::JNZ) {
+            return unsupported("end with their only jump, back to the start");
+        }
         // Now we know that our program does not do anything crazy
         // It is a do while loop with no other control flow
         // Note only instruction ADV can modify the A register (which is the loop variable)
         let adv: Vec<_> = blocks.iter().filter(|b| b.code == OpCode::ADV).collect();
-        assert_eq!(adv.len(), 1); // Only one update to A per iteration of the loop
-        let div: u64 = 1 << adv[0].arg;
-        assert!(div == 8); // Bottom 3 bits
+        // Only one update to A per iteration of the loop, shifting out the bottom 3 bits
+        if adv.len() != 1 || adv[0].arg != 3 {
+            return unsupported("shift A right by 3 bits once per loop");
+        }
+        // Each output takes 3 more bits of A, which has to fit in 64 bits
+        if self.code.len() > 21 {
+            return unsupported("output at most 21 numbers");
+        }
+        let div: u64 = 8;
         let output_length = self.code.len() as u32;
-        let a_lower_bound = div.pow(output_length - 1);
-        let a_upper_bound = div.pow(output_length);
+        let a_lower_bound = div.saturating_pow(output_length - 1);
+        let a_upper_bound = div.saturating_pow(output_length);
 
         // Build up by building the up the top 3 bits of A iteratively
         let part2 = self.recurse(1, 0, 0); // The first bit cannot be 0
-        if let Some(part2) = part2 {
-            assert!(part2 >= a_lower_bound && part2 <= a_upper_bound);
-        }
-        part2
+        let Some(part2) = part2 else {
+            return Err(Error::no_solution("no value of A makes the program quine"));
+        };
+        debug_assert!(part2 >= a_lower_bound && part2 <= a_upper_bound);
+        Ok(part2)
     }
     fn is_valid(&mut self, reg_a: u64, num_steps: u8) -> bool {
         self.set_state(reg_a);
         while self.step().is_some() {}
-        let out = self.check_output_tail(num_steps);
-        out
+        self.check_output_tail(num_steps)
     }
     /// Check that the last num_steps elements of the output buffer match the program data
     fn check_output_tail(&mut self, num_steps: u8) -> bool {
@@ -157,13 +180,10 @@ impl Machine {
     fn step(&mut self) -> Option<()> {
         let x1 = *self.code.get(self.ptr)?;
         let x2 = *self.code.get(self.ptr + 1)?;
-        let op = Operation::new(x1, x2);
+        let op = Operation::new(x1, x2)?;
 
         match op.code {
-            OpCode::ADV => {
-                let denom = 1 << self.combo(x2);
-                self.reg_a /= denom;
-            }
+            OpCode::ADV => self.reg_a = self.divide(x2),
             OpCode::BXL => self.reg_b ^= x2 as u64,
             OpCode::BST => self.reg_b = self.combo(x2) % 8,
             OpCode::JNZ => {
@@ -174,46 +194,150 @@ impl Machine {
             }
             OpCode::BXC => self.reg_b ^= self.reg_c,
             OpCode::OUT => self.out_buffer.push((self.combo(x2) % 8) as u8),
-            OpCode::BDV => {
-                let denom = 1 << self.combo(x2);
-                self.reg_b = self.reg_a / denom;
-            }
-            OpCode::CDV => {
-                let denom = 1 << self.combo(x2);
-                self.reg_c = self.reg_a / denom;
-            }
+            OpCode::BDV => self.reg_b = self.divide(x2),
+            OpCode::CDV => self.reg_c = self.divide(x2),
         }
         self.ptr += 2;
         Some(())
     }
 }
 
-pub fn solve(input: &str) -> Option<(String, u64)> {
-    let (reg_a, reg_b, reg_c) = input
-        .lines()
-        .take(3)
-        .map(|line| line.split_whitespace().last().unwrap().parse().ok())
-        .collect_tuple()?;
-
-    let code: Option<Vec<u8>> = input
-        .lines()
-        .nth(4)?
-        .split_whitespace()
-        .nth(1)?
-        .split(",")
-        .map(|x| x.parse().ok())
-        .collect();
-    let mut machine = Machine::new(reg_a?, reg_b?, reg_c?, code?);
+fn parse(input: &str) -> Result<Machine> {
+    let mut lines = input.lines().enumerate();
+    let mut registers = [0; 3];
+    for (register, name) in registers.iter_mut().zip(["A", "B", "C"]) {
+        let (line_idx, line) = lines.next().unwrap_or((0, ""));
+        let value = line
+            .strip_prefix("Register ")
+            .and_then(|rest| rest.strip_prefix(name))
+            .and_then(|rest| rest.strip_prefix(": "))
+            .ok_or_else(|| Error::parse(format!("expected register {}", name)).at_line(line_idx))?;
+        *register = parse_token(line_idx, line, value)?;
+    }
+    let (line_idx, line) = lines
+        .find(|(_, line)| !line.is_empty())
+        .ok_or_else(|| Error::parse("missing the program"))?;
+    let program = line
+        .strip_prefix("Program: ")
+        .ok_or_else(|| Error::parse("expected the program").at_line(line_idx))?;
+    let code: Vec<u8> = parse::comma_list(line_idx, line, program)?;
+    if code.iter().any(|&x| x > 7) {
+        return Err(Error::parse("expected 3-bit numbers").at_line(line_idx));
+    }
+    if !code.len().is_multiple_of(2) {
+        return Err(Error::parse("the last instruction has no operand").at_line(line_idx));
+    }
+    for (idx, s) in code.chunks_exact(2).enumerate() {
+        let op = Operation::new(s[0], s[1]).unwrap(); // 3-bit numbers are all opcodes
+        if op.has_combo_arg() && op.arg == 7 {
+            let message = format!("instruction {} uses reserved combo operand 7", idx);
+            return Err(Error::parse(message).at_line(line_idx));
+        }
+    }
+    let [reg_a, reg_b, reg_c] = registers;
+    Ok(Machine::new(reg_a, reg_b, reg_c, code))
+}
+
+pub fn part1(input: &str) -> Result<String> {
+    parse(input)?.run()
+}
+
+pub fn part2(input: &str) -> Result<u64> {
+    parse(input)?.search()
+}
+
+/// Both parts, failing as a whole when the part 2 search does not support the program, even
+/// though [`part1`] can still run it
+pub fn solve(input: &str) -> Result<(String, u64)> {
+    let mut machine = parse(input)?;
     let part1 = machine.run()?;
     let part2 = machine.search()?;
-    Some((part1, part2))
+    Ok((part1, part2))
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    fn day(&self) -> u8 {
+        17
+    }
+    fn title(&self) -> &'static str {
+        "Chronospatial Computer"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |mut machine| machine.run(),
+            |mut machine| machine.search(),
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p17.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0
+";
+    const EXAMPLE_PART2: &str = "\
+Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0
+";
+
+    #[test]
+    fn day17_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), "4,6,3,5,6,3,5,2,1,0");
+        assert_eq!(super::part2(EXAMPLE_PART2).unwrap(), 117440);
+    }
+
+    #[test]
+    fn day17_unsupported_search() {
+        // The first example shifts A by 1 bit per loop, which the search cannot undo
+        assert!(super::part1(EXAMPLE).is_ok());
+        let err = super::part2(EXAMPLE).unwrap_err();
+        assert!(matches!(err.kind(), crate::ErrorKind::NoSolution(_)));
+        assert_eq!(super::solve(EXAMPLE).unwrap_err(), err);
+    }
+
+    #[test]
+    fn day17_endless_loop() {
+        // Jumps back to the start without ever changing A
+        let input = EXAMPLE.replace("0,1,5,4,3,0", "5,4,3,0");
+        let err = super::part1(&input).unwrap_err();
+        assert!(matches!(err.kind(), crate::ErrorKind::NoSolution(_)));
+    }
+
     #[test]
     fn day17_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(17) else {
+            return;
+        };
+        crate::answers::assert_recorded(17, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day17_never_panics(input in crate::fuzz::mutations(EXAMPLE_PART2)) {
+            let _ = super::solve(&input);
+        }
     }
 }
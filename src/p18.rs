@@ -1,48 +1,20 @@
-use super::Direction;
-use itertools::Itertools;
+use super::{answer, answers, Answer, Error, Grid, Point, Result, Solution, Timings};
+use crate::error::parse_token;
+use crate::search::{self, Search};
+use crate::visual::{self, Animate, Animation, Cell, Color, Frame};
 use std::cmp::Ordering;
-use std::collections::BinaryHeap;
 
-const NUM_ROWS: usize = 71;
-const NUM_COLS: usize = 71;
-
-#[derive(PartialEq, Clone, Copy, Eq, PartialOrd, Ord, Debug)]
-struct Position {
-    row: usize,
-    col: usize,
-}
-
-impl Position {
-    fn new(row: usize, col: usize) -> Self {
-        Self { row, col }
-    }
-}
-
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct State {
-    cost: usize,
-    position: Position,
-}
-
-// From https://doc.rust-lang.org/nightly/std/collections/binary_heap/index.html#examples
-
-// The priority queue depends on `Ord`.
-// Explicitly implement the trait so the queue becomes a min-heap
-// instead of a max-heap.
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other
-            .cost
-            .cmp(&self.cost)
-            .then_with(|| self.position.cmp(&other.position))
-    }
+/// Dimensions of the memory space, and how many bytes have fallen before part 1
+#[derive(Debug, Clone, Copy)]
+struct Memory {
+    size: usize,
+    num_fallen: usize,
 }
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
+const MEMORY: Memory = Memory {
+    size: 71,
+    num_fallen: 1024,
+};
 
 #[derive(Debug, Clone, Copy)]
 enum Tile {
@@ -51,30 +23,38 @@ enum Tile {
     Goal,
 }
 
-struct Grid {
-    data: Vec<Tile>,
-    num_cols: usize,
+#[derive(Clone)]
+struct Space {
+    map: Grid<Tile>,
     remaining_commands: Vec<(usize, usize)>,
     solve_cache: Vec<Option<bool>>,
     last_ptr: usize,
 }
-impl Grid {
-    fn new(mut commands: Vec<(usize, usize)>) -> Self {
-        let mut data = vec![Tile::Empty; NUM_COLS * NUM_ROWS];
-        let num_cols = NUM_COLS;
-        *data.last_mut().unwrap() = Tile::Goal;
-        let mut grid = Self {
-            data,
-            num_cols,
+impl Space {
+    fn new(mut commands: Vec<(usize, usize)>, memory: Memory) -> Self {
+        let mut map = Grid::filled(memory.size, memory.size, Tile::Empty);
+        map[(memory.size - 1, memory.size - 1)] = Tile::Goal;
+        let mut space = Self {
+            map,
             last_ptr: 0,
-            solve_cache: vec![None; commands.len() - 1024],
+            solve_cache: vec![None; commands.len() - memory.num_fallen],
             remaining_commands: Vec::new(), // Slight lifetime hack
         };
-        for (row, col) in commands.drain(0..1024) {
-            grid.set_wall(row, col);
+        for (row, col) in commands.drain(0..memory.num_fallen) {
+            space.set_wall(row, col);
         }
-        grid.remaining_commands = commands;
-        grid
+        space.remaining_commands = commands;
+        space
+    }
+    /// Binary search for the first byte that cuts off the exit
+    fn first_blocker(&mut self) -> Option<String> {
+        let indices: Vec<_> = (0..self.remaining_commands.len()).collect();
+        let part2_idx = indices
+            .binary_search_by(|&idx| self.first_failure(idx))
+            .ok()?
+            .checked_sub(1)?; // Classic off-by-one error
+        let (row, col) = *self.remaining_commands.get(part2_idx)?;
+        Some(format!("{},{}", col, row))
     }
     fn first_failure(&mut self, idx: usize) -> Ordering {
         let outcome = self.do_solve(idx);
@@ -83,7 +63,7 @@ impl Grid {
             Ordering::Less
         } else {
             // Not solvable, check if its left neighbor was solvable
-            if self.do_solve(idx - 1) {
+            if idx.checked_sub(1).is_some_and(|prev| self.do_solve(prev)) {
                 // We're done
                 Ordering::Equal
             } else {
@@ -122,95 +102,189 @@ impl Grid {
         *self.get_mut(row, col) = Tile::Wall;
     }
     fn shortest_path(&self) -> Option<u64> {
-        // Djikstra's algorithm is not necessary here because the graph is unweighted
-        // But I didn't know if Part 2 would change that, and I started with Day 16's
-        // Code as a baseline
-        let mut dist = vec![usize::MAX; 4 * self.data.len()];
-
-        let start_node = Position::new(0, 0);
-        let start = self.get_pos_idx(start_node);
-        let mut heap = BinaryHeap::new();
-        dist[start] = 0;
-        heap.push(State {
-            cost: 0,
-            position: start_node,
-        });
-        // Examine the frontier with lower cost nodes first (min-heap)
-        while let Some(State { cost, position }) = heap.pop() {
-            if let Tile::Goal = self.get(position.row, position.col) {
-                return Some(cost as u64);
-            }
-
-            // Bad node, look no further
-            if cost > dist[self.get_pos_idx(position)] {
-                continue;
-            }
-            let adj_list = Direction::orthogonal((position.row, position.col));
-            for edge in &adj_list {
-                // Check in bounds
-                if edge.0 >= NUM_ROWS || edge.1 >= NUM_COLS {
-                    continue;
-                }
-                if let Tile::Wall = self.get(edge.0, edge.1) {
-                    continue;
-                }
-                let next = State {
-                    cost: cost + 1,
-                    position: Position::new(edge.0, edge.1),
-                };
-
-                let next_idx = self.get_pos_idx(next.position);
-                if next.cost < dist[next_idx] {
-                    // Update
-                    heap.push(next);
-                    dist[next_idx] = next.cost;
-                }
-            }
-        }
-        None
+        let search = self.search();
+        search.distance(search.goal()?)
     }
-    fn get_pos_idx(&self, pos: Position) -> usize {
-        self.get_simple_idx(pos.row, pos.col)
+    fn search(&self) -> Search<(usize, usize)> {
+        let open = |position| {
+            self.map
+                .neighbors(position)
+                .filter(|&(row, col)| !matches!(self.get(row, col), Tile::Wall))
+        };
+        let is_exit = |(row, col)| matches!(self.get(row, col), Tile::Goal);
+        search::bfs((0, 0), open, is_exit)
     }
-    fn get_simple_idx(&self, row: usize, col: usize) -> usize {
-        row * self.num_cols + col
+    fn frame(&self) -> Frame {
+        Frame::new(&self.map, |tile| match tile {
+            Tile::Empty => Cell::plain('.'),
+            Tile::Wall => Cell::new('#', Color::Gray),
+            Tile::Goal => Cell::new('E', Color::Red),
+        })
     }
     fn get_mut(&mut self, row: usize, col: usize) -> &mut Tile {
-        let idx = self.get_simple_idx(row, col);
-        &mut self.data[idx]
+        &mut self.map[(row, col)]
     }
     fn get(&self, row: usize, col: usize) -> Tile {
-        self.data[self.get_simple_idx(row, col)]
+        self.map[(row, col)]
     }
 }
 
-pub fn solve(input: &str) -> Option<(u64, String)> {
+fn parse(input: &str, memory: Memory) -> Result<Space> {
     let mut commands = Vec::new();
-    for data in input
-        .lines()
-        .map(|line| line.split(",").map(|x| x.parse().ok()).collect_tuple())
-    {
-        let (row, col) = data?;
-        commands.push((row?, col?));
-    }
-    let mut grid = Grid::new(commands);
-
-    let part1 = grid.shortest_path()?;
-
-    let indices: Vec<_> = (0..grid.remaining_commands.len()).collect();
-    let part2_idx = indices
-        .binary_search_by(|&idx| grid.first_failure(idx))
-        .ok()?
-        - 1; // Classic off-by-one error
-    let part2 = *grid.remaining_commands.get(part2_idx)?;
-    Some((part1, format!("{},{}", part2.0, part2.1)))
+    for (line_idx, line) in input.lines().enumerate() {
+        let (x, y) = line
+            .split_once(',')
+            .ok_or_else(|| Error::parse("expected a coordinate pair").at_line(line_idx))?;
+        let byte = Point::new(
+            parse_token(line_idx, line, x)?,
+            parse_token(line_idx, line, y)?,
+        );
+        let position = byte
+            .to_grid((memory.size, memory.size))
+            .ok_or_else(|| Error::parse("byte falls outside the memory space").at_line(line_idx))?;
+        commands.push(position);
+    }
+    if commands.len() < memory.num_fallen {
+        let message = format!("expected at least {} bytes", memory.num_fallen);
+        return Err(Error::parse(message));
+    }
+    Ok(Space::new(commands, memory))
+}
+
+fn no_path() -> Error {
+    Error::no_solution("the exit cannot be reached")
+}
+
+fn never_blocked() -> Error {
+    Error::no_solution("no byte cuts off the exit")
+}
+
+pub fn part1(input: &str) -> Result<u64> {
+    parse(input, MEMORY)?.shortest_path().ok_or_else(no_path)
+}
+
+pub fn part2(input: &str) -> Result<String> {
+    parse(input, MEMORY)?
+        .first_blocker()
+        .ok_or_else(never_blocked)
+}
+
+pub fn solve(input: &str) -> Result<(u64, String)> {
+    let mut space = parse(input, MEMORY)?;
+    let part1 = space.shortest_path().ok_or_else(no_path)?;
+    let part2 = space.first_blocker().ok_or_else(never_blocked)?;
+    Ok((part1, part2))
+}
+
+/// Play the search from the corner once the first bytes have fallen, one step per frame, then
+/// show the shortest path
+pub fn animate(input: &str, animation: &mut Animation) -> Result<()> {
+    let space = parse(input, MEMORY)?;
+    let search = space.search();
+    let mut frame = space.frame();
+    for (steps, positions) in visual::waves(&search, |position| position) {
+        for position in positions {
+            frame.set(position, Cell::new('o', Color::Cyan));
+        }
+        animation.show(|| frame.clone().caption(format!("{} steps", steps)));
+    }
+    let goal = search.goal().ok_or_else(no_path)?;
+    for position in search.path(goal).ok_or_else(no_path)? {
+        frame.set(position, Cell::new('O', Color::Green));
+    }
+    let steps = search.distance(goal).ok_or_else(no_path)?;
+    animation.show_always(frame.caption(format!("the exit is {} steps away", steps)));
+    Ok(())
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    fn day(&self) -> u8 {
+        18
+    }
+    fn title(&self) -> &'static str {
+        "RAM Run"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            |input| parse(input, MEMORY),
+            |space| space.shortest_path(),
+            |mut space| space.first_blocker(),
+        )
+    }
+    fn animate(&self) -> Option<Animate> {
+        Some(animate)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p18.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+5,4
+4,2
+4,5
+3,0
+2,1
+6,3
+2,4
+1,5
+0,6
+3,3
+2,6
+5,1
+1,2
+5,5
+2,5
+6,5
+1,4
+0,4
+6,4
+1,1
+6,1
+1,0
+0,5
+1,6
+2,0
+";
+
+    #[test]
+    fn day18_example() {
+        // The example memory space is only 7 wide, and part 1 stops after 12 bytes
+        let memory = super::Memory {
+            size: 7,
+            num_fallen: 12,
+        };
+        let mut space = super::parse(EXAMPLE, memory).unwrap();
+        assert_eq!(space.shortest_path(), Some(22));
+        assert_eq!(space.first_blocker().as_deref(), Some("6,1"));
+    }
+
     #[test]
     fn day18_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(18) else {
+            return;
+        };
+        crate::answers::assert_recorded(18, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day18_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,67 +1,214 @@
+use crate::error::column;
+use crate::{answer, answers, Answer, Error, Result, Solution, Timings};
+
 const NUM_COLORS: usize = 5;
 
-fn get_color_idx(b: u8) -> usize {
+fn get_color_idx(b: u8) -> Option<usize> {
     match b {
-        b'w' => 0,
-        b'u' => 1,
-        b'b' => 2,
-        b'r' => 3,
-        b'g' => 4,
-        _ => unimplemented!(),
+        b'w' => Some(0),
+        b'u' => Some(1),
+        b'b' => Some(2),
+        b'r' => Some(3),
+        b'g' => Some(4),
+        _ => None,
+    }
+}
+
+/// Check that every stripe has a known color, pointing at the first that does not
+fn check_colors(line_idx: usize, line: &str, stripes: &str) -> Result<()> {
+    match stripes.bytes().position(|b| get_color_idx(b).is_none()) {
+        None => Ok(()),
+        Some(idx) => {
+            let c = stripes[idx..].chars().next().unwrap_or_default();
+            Err(Error::unexpected_char(c).at(line_idx, column(line, &stripes[idx..])))
+        }
     }
 }
 
-fn parse_string(s: &[u8], valid: &[Vec<&[u8]>; NUM_COLORS]) -> u64 {
-    let mut solves = vec![0; s.len() + 1];
-    solves[s.len()] = 1;
+/// Ways to make the design out of towels, or `None` if there are too many to count in 64 bits
+fn parse_string(s: &[u8], valid: &[Vec<&[u8]>; NUM_COLORS]) -> Option<u64> {
+    // Ways to make each suffix, `None` once there are too many, which still tells a suffix
+    // that can be made from one that cannot
+    let mut solves = vec![Some(0u64); s.len() + 1];
+    solves[s.len()] = Some(1);
     for i in (0..s.len()).rev() {
-        let first = get_color_idx(s[i]);
+        let Some(first) = get_color_idx(s[i]) else {
+            continue; // No towel has this color
+        };
         for &legal in valid[first].iter() {
             let end_idx = i + legal.len();
             if end_idx > s.len() {
                 break; // Trying to match a string that is longer than our substring
             }
-            if solves[end_idx] == 0 {
+            if solves[end_idx] == Some(0) {
                 continue; // Even if we match this substring, the end would not be valid
             }
             let substr = &s[i..end_idx];
             if substr == legal {
                 // Found a valid match
-                solves[i] += solves[end_idx];
+                solves[i] = solves[i]
+                    .zip(solves[end_idx])
+                    .and_then(|(a, b)| a.checked_add(b));
             }
         }
     }
     solves[0]
 }
 
-pub fn solve(input: &str) -> Option<(u64, u64)> {
-    let mut strings: [Vec<&[u8]>; NUM_COLORS] = Default::default();
-    for data in input.lines().nth(0)?.split(", ") {
+#[derive(Clone)]
+struct Onsen<'a> {
+    /// Towel patterns grouped by their first color, shortest first
+    towels: [Vec<&'a [u8]>; NUM_COLORS],
+    designs: Vec<&'a [u8]>,
+}
+
+fn parse(input: &str) -> Result<Onsen<'_>> {
+    let mut towels: [Vec<&[u8]>; NUM_COLORS] = Default::default();
+    let line = input.lines().next().unwrap_or_default();
+    for data in line.split(", ") {
+        check_colors(0, line, data)?;
         let bytes = data.as_bytes();
-        let first = bytes[0];
-        strings[get_color_idx(first)].push(bytes);
+        let Some(first) = bytes.first().and_then(|&b| get_color_idx(b)) else {
+            return Err(Error::parse("found an empty towel pattern").at_line(0));
+        };
+        towels[first].push(bytes);
     }
-    for v in strings.iter_mut() {
+    for v in towels.iter_mut() {
         v.sort_by_key(|s| s.len());
     }
+    let mut designs = Vec::new();
+    for (line_idx, line) in input.lines().enumerate().skip(2) {
+        check_colors(line_idx, line, line)?;
+        designs.push(line.as_bytes());
+    }
+    Ok(Onsen { towels, designs })
+}
+
+fn too_many() -> Error {
+    Error::no_solution("the arrangements do not fit in 64 bits")
+}
+
+impl Onsen<'_> {
+    /// Ways to make each design, `None` for those with too many to count
+    fn arrangements(&self) -> impl Iterator<Item = Option<u64>> + '_ {
+        self.designs
+            .iter()
+            .map(|bytes| parse_string(bytes, &self.towels))
+    }
+    /// Designs that can be made at all, however many ways there are
+    fn possible(&self) -> u64 {
+        self.arrangements()
+            .filter(|&outcome| outcome != Some(0))
+            .count() as u64
+    }
+    fn total(&self) -> Result<u64> {
+        self.arrangements()
+            .try_fold(0u64, |total, outcome| total.checked_add(outcome?))
+            .ok_or_else(too_many)
+    }
+}
+
+pub fn part1(input: &str) -> Result<u64> {
+    Ok(parse(input)?.possible())
+}
+
+pub fn part2(input: &str) -> Result<u64> {
+    parse(input)?.total()
+}
+
+pub fn solve(input: &str) -> Result<(u64, u64)> {
     let mut part1 = 0;
-    let mut part2 = 0;
-    for s in input.lines().skip(2) {
-        let bytes = s.as_bytes();
-        let outcome = parse_string(bytes, &strings);
-        if outcome > 0 {
+    let mut part2 = Some(0u64);
+    for outcome in parse(input)?.arrangements() {
+        if outcome != Some(0) {
             part1 += 1;
         }
-        part2 += outcome;
+        part2 = part2
+            .zip(outcome)
+            .and_then(|(total, ways)| total.checked_add(ways));
+    }
+    Ok((part1, part2.ok_or_else(too_many)?))
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    fn day(&self) -> u8 {
+        19
+    }
+    fn title(&self) -> &'static str {
+        "Linen Layout"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |onsen| onsen.possible(),
+            |onsen| onsen.total(),
+        )
     }
-    Some((part1, part2))
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p19.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+r, wr, b, g, bwu, rb, gb, br
+
+brwrr
+bggr
+gbbr
+rrbgbr
+ubwu
+bwurrg
+brgr
+bbrgwb
+";
+
+    #[test]
+    fn day19_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 6);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 16);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (6, 16));
+    }
+
+    #[test]
+    fn day19_too_many_arrangements() {
+        // A hundred stripes make more than 2^64 sequences of one and two stripe towels
+        let long = "r".repeat(100);
+        let input = format!("r, rr\n\n{}\nb{}\nrr\n", long, long);
+        assert_eq!(super::part1(&input).unwrap(), 2);
+        let err = super::part2(&input).unwrap_err();
+        assert!(matches!(err.kind(), crate::ErrorKind::NoSolution(_)));
+        assert!(super::solve(&input).is_err());
+        // Still exact when the stripes that overflow cannot be reached
+        let input = format!("r, rr\n\nb{}\nrr\n", long);
+        assert_eq!(super::solve(&input).unwrap(), (1, 2));
+    }
+
     #[test]
     fn day19_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(19) else {
+            return;
+        };
+        crate::answers::assert_recorded(19, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day19_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,74 +1,179 @@
-pub fn solve(input: &str) -> Option<(usize, usize)> {
+use crate::error::parse_token;
+use crate::{answer, answers, Answer, Result, Solution, Timings};
+
+fn parse(input: &str) -> Result<Vec<Vec<i64>>> {
     input
         .lines()
-        .map(|line| {
-            let vec = parse_line(line);
-            let (part1, part2) = is_gradual(&vec);
-            (part1 as usize, part2 as usize)
-        })
-        .reduce(|acc, e| (acc.0 + e.0, acc.1 + e.1))
+        .enumerate()
+        .map(|(line_idx, line)| parse_line(line_idx, line))
+        .collect()
 }
 
-fn parse_line(line: &str) -> Vec<i32> {
-    let diffs: Vec<_> = line
+/// Differences between neighboring levels, wide enough that any two levels fit
+fn parse_line(line_idx: usize, line: &str) -> Result<Vec<i64>> {
+    let levels = line
         .split_whitespace()
-        .map(|x| x.parse::<i32>().unwrap())
+        .map(|x| parse_token::<i32>(line_idx, line, x))
+        .collect::<Result<Vec<_>>>()?;
+    let diffs: Vec<_> = levels
+        .into_iter()
         .scan(0, |state, val| {
+            let val = i64::from(val);
             let diff = *state - val;
             *state = val;
             Some(diff)
         })
         .collect();
-    diffs
+    Ok(diffs)
 }
 
-fn is_gradual(diffs: &[i32]) -> (bool, bool) {
-    let f1 = |&diff| (diff > 0) && (diff <= 3);
-    let f2 = |&diff| (diff < 0) && (diff >= -3);
-    let incr = diffs.iter().skip(1).all(f1);
-    let decr = diffs.iter().skip(1).all(f2);
-    let part1 = incr || decr;
-    if !part1 {
-        // Relax the restrictions
-        let mut part2 = false;
-        let incr = diffs.iter().skip(2).all(f1);
-        let decr = diffs.iter().skip(2).all(f2);
-        // Try removing element 0
-        if incr || decr {
-            return (true, true);
+fn is_gradual(diffs: &[i64]) -> bool {
+    let incr = diffs.iter().skip(1).all(|&diff| (diff > 0) && (diff <= 3));
+    let decr = diffs.iter().skip(1).all(|&diff| (-3..0).contains(&diff));
+    incr || decr
+}
+
+fn is_gradual_dampened(diffs: &[i64]) -> bool {
+    if is_gradual(diffs) {
+        return true;
+    }
+    // Relax the restrictions
+    // Try removing element 0
+    if is_gradual(&diffs[1..]) {
+        return true;
+    }
+    // If not, try removing the remaining
+    for i in 1..diffs.len() {
+        let mut changed = Vec::from_iter(diffs.iter().copied());
+        let val = changed[i];
+        if let Some(x) = changed.get_mut(i + 1) {
+            *x += val;
         }
-        // If not, try removing the remaining
-        for i in 1..diffs.len() {
-            let mut changed = Vec::from_iter(diffs.iter().copied());
-            let val = changed[i];
-            if let Some(x) = changed.get_mut(i + 1) {
-                *x += val;
-            }
-            changed.swap_remove(i);
-            let incr = changed
-                .iter()
-                .skip(1)
-                .all(|&diff| (diff > 0) && (diff <= 3)); // Random lifetime issue if using f1
-            let decr = changed
-                .iter()
-                .skip(1)
-                .all(|&diff| (diff < 0) && (diff >= -3)); // Random lifetime issue if using f2
-            if incr || decr {
-                part2 = true;
-                break;
-            }
+        changed.swap_remove(i);
+        if is_gradual(&changed) {
+            return true;
         }
-        (false, part2)
-    } else {
-        (true, true)
+    }
+    false
+}
+
+pub fn part1(input: &str) -> Result<usize> {
+    let reports = parse(input)?;
+    Ok(reports.iter().filter(|diffs| is_gradual(diffs)).count())
+}
+
+pub fn part2(input: &str) -> Result<usize> {
+    let reports = parse(input)?;
+    Ok(reports
+        .iter()
+        .filter(|diffs| is_gradual_dampened(diffs))
+        .count())
+}
+
+pub fn solve(input: &str) -> Result<(usize, usize)> {
+    let reports = parse(input)?;
+    let part1 = reports.iter().filter(|diffs| is_gradual(diffs)).count();
+    let part2 = reports
+        .iter()
+        .filter(|diffs| is_gradual_dampened(diffs))
+        .count();
+    Ok((part1, part2))
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    fn day(&self) -> u8 {
+        2
+    }
+    fn title(&self) -> &'static str {
+        "Red-Nosed Reports"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |reports| reports.iter().filter(|diffs| is_gradual(diffs)).count(),
+            |reports| {
+                reports
+                    .iter()
+                    .filter(|diffs| is_gradual_dampened(diffs))
+                    .count()
+            },
+        )
     }
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p2.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+7 6 4 2 1
+1 2 7 8 9
+9 7 6 2 1
+1 3 2 4 5
+8 6 4 4 1
+1 3 6 7 9
+";
+
+    #[test]
+    fn day2_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 2);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 4);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (2, 4));
+    }
+
     #[test]
     fn day2_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(2) else {
+            return;
+        };
+        crate::answers::assert_recorded(2, super::solve(&input).unwrap());
+    }
+
+    /// Check the levels themselves rather than their differences
+    fn naive_safe(levels: &[i32]) -> bool {
+        let steps: Vec<_> = levels.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        steps.iter().all(|step| (1..=3).contains(step))
+            || steps.iter().all(|step| (-3..=-1).contains(step))
+    }
+
+    /// Try the report without each level in turn
+    fn naive_dampened(levels: &[i32]) -> bool {
+        naive_safe(levels)
+            || (0..levels.len()).any(|skip| {
+                let mut fewer = levels.to_vec();
+                fewer.remove(skip);
+                naive_safe(&fewer)
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn day2_matches_naive(levels in prop::collection::vec(0..10, 1..9)) {
+            // Small levels, so steps are often just inside or outside the allowed range
+            let line = levels.iter().map(i32::to_string).collect::<Vec<_>>().join(" ");
+            let diffs = super::parse_line(0, &line).unwrap();
+            prop_assert_eq!(super::is_gradual(&diffs), naive_safe(&levels));
+            prop_assert_eq!(super::is_gradual_dampened(&diffs), naive_dampened(&levels));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn day2_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,7 +1,12 @@
-use crate::Direction;
-use std::collections::VecDeque;
+use crate::image::{Image, Picture, Rgb};
+use crate::search;
+use crate::{
+    answer, answers, parallel, parse, Answer, Direction, Error, Grid, Result, Solution, Timings,
+};
 
 const MAX_CHEAT: usize = 20;
+/// Only count cheats that save at least this many picoseconds
+const MIN_SAVING: u64 = 100;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Tile {
@@ -9,18 +14,6 @@ enum Tile {
     Wall,
 }
 
-#[derive(Debug, Clone)]
-struct SearchNode {
-    node: (usize, usize),
-    dist: usize,
-}
-
-impl SearchNode {
-    fn new(node: (usize, usize), dist: usize) -> Self {
-        Self { node, dist }
-    }
-}
-
 struct Origin((usize, usize));
 
 impl IntoIterator for Origin {
@@ -74,129 +67,217 @@ impl std::iter::Iterator for DistanceIterator {
     }
 }
 
-struct Grid {
-    data: Vec<Tile>,
-    num_cols: usize,
-    num_rows: usize,
+#[derive(Clone)]
+struct Track {
+    map: Grid<Tile>,
     end: (usize, usize),
 }
-impl Grid {
-    fn new(data: Vec<Tile>, num_rows: usize, end: (usize, usize)) -> Self {
-        let num_cols = data.len() / num_rows;
-        Self {
-            data,
-            num_cols,
-            num_rows,
-            end,
-        }
-    }
+impl Track {
+    /// Distance from every tile to the end, `u64::MAX` where the end cannot be reached
     fn all_shortest(&self) -> Vec<u64> {
-        let mut out = vec![u64::MAX; self.data.len()];
-        self.bfs(&mut out); // BFS gives optimal shortest distances in an unweighted graph
-        out
-    }
-    fn bfs(&self, distances: &mut [u64]) {
-        // Memory should not explode because the grid isn't that big
-        let mut queue = VecDeque::new();
-        distances[self.get_simple_idx(self.end.0, self.end.1)] = 0;
-        queue.push_back(SearchNode::new(self.end, 0));
-        while let Some(SearchNode { node, dist }) = queue.pop_front() {
-            let neighbors = Direction::orthogonal(node);
-            for next in neighbors {
-                let (n_row, n_col) = next;
-                if let Tile::Empty = self.get(n_row, n_col) {
-                    let neighbor_idx = self.get_simple_idx(n_row, n_col);
-                    // If the distance is max, we have not visited it yet
-                    if distances[neighbor_idx] == u64::MAX {
-                        queue.push_back(SearchNode::new(next, dist + 1));
-                        distances[self.get_simple_idx(n_row, n_col)] = (dist + 1) as u64;
-                    }
-                }
-            }
+        let open = |position| {
+            self.map
+                .neighbors(position)
+                .filter(|&next| self.map[next] == Tile::Empty)
+        };
+        // Search the whole track, as every tile's distance is needed
+        let search = search::bfs(self.end, open, |_| false);
+        let mut out = vec![u64::MAX; self.map.len()];
+        for (&position, &dist) in search.distances() {
+            out[self.map.index_of(position)] = dist;
         }
+        out
     }
-    fn solve_part1(&self, distances: &[u64]) -> u64 {
-        self.find_cheats(distances, 2, Direction::wide_orthongonal)
+    fn solve_part1(&self, distances: &[u64], min_saving: u64) -> u64 {
+        let two_steps = |origin| Direction::CARDINAL.map(|d| d.get_square(d.get_square(origin)));
+        self.find_cheats(distances, 2, min_saving, two_steps)
     }
-    fn solve_part2(&self, distances: &[u64]) -> u64 {
-        self.find_cheats(distances, MAX_CHEAT, |x| Origin(x))
+    fn solve_part2(&self, distances: &[u64], min_saving: u64) -> u64 {
+        self.find_cheats(distances, MAX_CHEAT, min_saving, Origin)
     }
-    fn find_cheats<I, F>(&self, distances: &[u64], cheat_dist: usize, construct: F) -> u64
+    fn find_cheats<I, F>(
+        &self,
+        distances: &[u64],
+        cheat_dist: usize,
+        min_saving: u64,
+        construct: F,
+    ) -> u64
     where
         I: IntoIterator<Item = (usize, usize)>,
-        F: Fn((usize, usize)) -> I,
+        F: Fn((usize, usize)) -> I + Sync,
     {
-        let mut count = 0;
-        for row in 0..self.num_rows {
-            for col in 0..self.num_cols {
-                if let Tile::Empty = self.get(row, col) {
-                    let dist1 = distances[self.get_simple_idx(row, col)];
-                    let reachable = construct((row, col));
-                    for (n_row, n_col) in reachable {
-                        // Check in bounds, and that it is empty
-                        if self.is_empty(n_row, n_col) {
-                            let dist2 = distances[self.get_simple_idx(n_row, n_col)];
-                            let manhattan_dist = n_row.abs_diff(row) + n_col.abs_diff(col);
-                            if manhattan_dist > cheat_dist {
-                                continue;
-                            }
-                            if dist2
-                                .saturating_sub(dist1)
-                                .saturating_sub(manhattan_dist as u64)
-                                >= 100
-                            {
-                                count += 1;
-                            }
+        let track: Vec<_> = self
+            .map
+            .iter()
+            .filter(|(_, tile)| **tile == Tile::Empty)
+            .map(|(position, _)| position)
+            .collect();
+        let counts = parallel::map_chunks(&track, |chunk| {
+            let mut count = 0;
+            for &(row, col) in chunk {
+                let dist1 = distances[self.map.index_of((row, col))];
+                let reachable = construct((row, col));
+                for (n_row, n_col) in reachable {
+                    // Check in bounds, and that it is empty
+                    if self.is_empty(n_row, n_col) {
+                        let dist2 = distances[self.map.index_of((n_row, n_col))];
+                        let manhattan_dist = n_row.abs_diff(row) + n_col.abs_diff(col);
+                        if manhattan_dist > cheat_dist {
+                            continue;
+                        }
+                        if dist2
+                            .saturating_sub(dist1)
+                            .saturating_sub(manhattan_dist as u64)
+                            >= min_saving
+                        {
+                            count += 1;
                         }
                     }
                 }
             }
-        }
-        count
+            count
+        });
+        counts.into_iter().sum()
     }
     fn is_empty(&self, row: usize, col: usize) -> bool {
-        row < self.num_rows && col < self.num_cols && self.get(row, col) != Tile::Wall
-    }
-    fn get_simple_idx(&self, row: usize, col: usize) -> usize {
-        row * self.num_cols + col
+        self.map.get((row, col)) == Some(&Tile::Empty)
     }
-    fn get(&self, row: usize, col: usize) -> Tile {
-        self.data[self.get_simple_idx(row, col)]
+}
+
+fn parse(input: &str) -> Result<Track> {
+    let tile = |c| match c {
+        '#' => Ok(Tile::Wall),
+        '.' | 'S' | 'E' => Ok(Tile::Empty),
+        _ => Err(Error::unexpected_char(c)),
+    };
+    let (map, [end]) = parse::grid_with_markers(input.lines().enumerate(), ['E'], tile)?;
+    // The search never bounds checks, so it relies on the track being walled in
+    let enclosed = map
+        .iter()
+        .all(|(position, tile)| !map.on_edge(position) || *tile == Tile::Wall);
+    if !enclosed {
+        return Err(Error::parse("the track is not surrounded by walls"));
     }
+    Ok(Track { map, end })
 }
 
-pub fn solve(input: &str) -> Option<(u64, u64)> {
-    let mut end = (0, 0);
-    let mut grid_data = Vec::new();
-    let mut num_rows = 0;
-    for (row, line) in input.lines().enumerate() {
-        for (col, c) in line.as_bytes().iter().copied().enumerate() {
-            let tile = match c {
-                b'#' => Tile::Wall,
-                b'.' => Tile::Empty,
-                b'S' => Tile::Empty,
-                b'E' => {
-                    end = (row, col);
-                    Tile::Empty
-                }
-                _ => unimplemented!(),
-            };
-            grid_data.push(tile);
+pub fn part1(input: &str) -> Result<u64> {
+    let track = parse(input)?;
+    Ok(track.solve_part1(&track.all_shortest(), MIN_SAVING))
+}
+
+pub fn part2(input: &str) -> Result<u64> {
+    let track = parse(input)?;
+    Ok(track.solve_part2(&track.all_shortest(), MIN_SAVING))
+}
+
+pub fn solve(input: &str) -> Result<(u64, u64)> {
+    let track = parse(input)?;
+    let all_shortest = track.all_shortest();
+    let part1 = track.solve_part1(&all_shortest, MIN_SAVING);
+    let part2 = track.solve_part2(&all_shortest, MIN_SAVING);
+    Ok((part1, part2))
+}
+
+/// Picture the track as a heat map of the distance to the end, from blue near it to red
+pub fn image(input: &str) -> Result<Image> {
+    let track = parse(input)?;
+    let distances = track.all_shortest();
+    let farthest = distances
+        .iter()
+        .copied()
+        .filter(|&distance| distance != u64::MAX)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut image = Image::new(&track.map, |_| Rgb::BLACK);
+    for (idx, &distance) in distances.iter().enumerate() {
+        if distance != u64::MAX {
+            let heat = Rgb::heat(distance as f64 / farthest as f64);
+            image.set(track.map.position(idx), heat);
         }
-        num_rows += 1;
     }
-    let grid = Grid::new(grid_data, num_rows, end);
-    let all_shortest = grid.all_shortest();
-    let part1 = grid.solve_part1(&all_shortest);
-    let part2 = grid.solve_part2(&all_shortest);
-    Some((part1, part2))
+    Ok(image)
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    fn day(&self) -> u8 {
+        20
+    }
+    fn title(&self) -> &'static str {
+        "Race Condition"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |track| track.solve_part1(&track.all_shortest(), MIN_SAVING),
+            |track| track.solve_part2(&track.all_shortest(), MIN_SAVING),
+        )
+    }
+    fn picture(&self) -> Option<Picture> {
+        Some(image)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p20.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############
+";
+
+    #[test]
+    fn day20_example() {
+        // The example track is too short for any cheat to save 100 picoseconds
+        let track = super::parse(EXAMPLE).unwrap();
+        let distances = track.all_shortest();
+        assert_eq!(track.solve_part1(&distances, 64), 1);
+        assert_eq!(track.solve_part1(&distances, 20), 5);
+        assert_eq!(track.solve_part1(&distances, 2), 44);
+        assert_eq!(track.solve_part2(&distances, 76), 3);
+        assert_eq!(track.solve_part2(&distances, 50), 285);
+    }
+
     #[test]
     fn day20_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(20) else {
+            return;
+        };
+        crate::answers::assert_recorded(20, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day20_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,6 +1,6 @@
 use itertools::Itertools;
 
-use crate::Direction;
+use crate::{answer, answers, Answer, Direction, Error, Result, Solution, Timings};
 use std::collections::HashMap;
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
@@ -16,9 +16,10 @@ enum PadVal {
 impl PadVal {
     fn step(self, origin: (usize, usize)) -> (usize, usize) {
         match self {
-            PadVal::Empty => unimplemented!(),
+            PadVal::Empty | PadVal::A => {
+                unreachable!("{:?} is not a move, get_steps only yields arrows", self)
+            }
             PadVal::Up => Direction::North.get_square(origin),
-            PadVal::A => unimplemented!(),
             PadVal::Left => Direction::West.get_square(origin),
             PadVal::Down => Direction::South.get_square(origin),
             PadVal::Right => Direction::East.get_square(origin),
@@ -50,16 +51,15 @@ impl NumPad {
     fn new(parent: DirPad) -> Self {
         let data = [[7, 8, 9], [4, 5, 6], [1, 2, 3], [255, 0, 10]];
         let mut lookup = [Default::default(); 11];
-        for i in 0..4 {
-            for j in 0..3 {
-                let val = data[i][j];
+        for (i, row) in data.iter().enumerate() {
+            for (j, &val) in row.iter().enumerate() {
                 if val != 255 {
                     lookup[val as usize] = (i, j);
                 }
             }
         }
         Self {
-            lookup: lookup,
+            lookup,
             location: 10,
             parent,
         }
@@ -246,9 +246,8 @@ impl DirPad {
             [PadVal::Left, PadVal::Down, PadVal::Right],
         ];
         let mut lookup = [Default::default(); 6];
-        for i in 0..2 {
-            for j in 0..3 {
-                let val = data[i][j];
+        for (i, row) in data.iter().enumerate() {
+            for (j, &val) in row.iter().enumerate() {
                 lookup[val as usize] = (i, j);
             }
         }
@@ -309,32 +308,127 @@ fn build_numpad(depth: usize) -> NumPad {
     NumPad::new(root)
 }
 
-fn depth_solve(input: &str, depth: usize) -> Option<u64> {
+#[derive(Clone)]
+/// A door code as keypad digits (A is 10), with its numeric part
+struct Code {
+    keys: Vec<u8>,
+    numeric: u64,
+}
+
+fn parse(input: &str) -> Result<Vec<Code>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(line_idx, line)| {
+            let keys = line
+                .chars()
+                .enumerate()
+                .map(|(col_idx, x)| match x {
+                    '0'..='9' | 'A' => Ok(x.to_digit(16).unwrap() as u8),
+                    _ => Err(Error::unexpected_char(x).at(line_idx, col_idx)),
+                })
+                .collect::<Result<_>>()?;
+            let numeric: String = line.chars().filter(|x| x.is_ascii_digit()).collect();
+            let numeric = numeric
+                .parse::<u64>()
+                .map_err(|_| Error::parse("code has no numeric part").at_line(line_idx))?;
+            Ok(Code { keys, numeric })
+        })
+        .collect()
+}
+
+fn depth_solve(codes: &[Code], depth: usize) -> Result<u64> {
     let mut numpad = build_numpad(depth);
-    let mut ans = 0;
-    for line in input.lines() {
-        let seq = line.chars().map(|x| x.to_digit(16).unwrap() as u8);
-        let numeric: String = line.chars().filter(|x| x.is_ascii_digit()).collect();
-        let numeric_num = u64::from_str_radix(&numeric, 10).ok()?;
-        let mut cost = 0;
-        for dest in seq {
-            cost += numpad.next_number(dest);
+    let mut ans: u64 = 0;
+    let overflow = || Error::no_solution("the complexities do not fit in 64 bits");
+    for code in codes {
+        let mut cost: u64 = 0;
+        for dest in code.keys.iter().copied() {
+            cost = cost
+                .checked_add(numpad.next_number(dest))
+                .ok_or_else(overflow)?;
         }
-        ans += cost * numeric_num;
+        let complexity = cost.checked_mul(code.numeric).ok_or_else(overflow)?;
+        ans = ans.checked_add(complexity).ok_or_else(overflow)?;
         numpad.reset();
     }
-    Some(ans)
+    Ok(ans)
+}
+
+pub fn part1(input: &str) -> Result<u64> {
+    depth_solve(&parse(input)?, 2)
+}
+
+pub fn part2(input: &str) -> Result<u64> {
+    depth_solve(&parse(input)?, 25)
 }
 
-pub fn solve(input: &str) -> Option<(u64, u64)> {
-    Some((depth_solve(input, 2)?, depth_solve(input, 25)?))
+pub fn solve(input: &str) -> Result<(u64, u64)> {
+    let codes = parse(input)?;
+    Ok((depth_solve(&codes, 2)?, depth_solve(&codes, 25)?))
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    fn day(&self) -> u8 {
+        21
+    }
+    fn title(&self) -> &'static str {
+        "Keypad Conundrum"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |codes| depth_solve(&codes, 2),
+            |codes| depth_solve(&codes, 25),
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p21.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+029A
+980A
+179A
+456A
+379A
+";
+
+    #[test]
+    fn day21_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 126384);
+        // No published answer for part 2, pinned to catch regressions
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 154115708116294);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (126384, 154115708116294));
+    }
+
     #[test]
     fn day21_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(21) else {
+            return;
+        };
+        crate::answers::assert_recorded(21, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day21_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
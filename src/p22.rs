@@ -1,3 +1,5 @@
+use crate::error::parse_token;
+use crate::{answer, answers, parallel, Answer, Error, Result, Solution, Timings};
 use std::collections::HashMap;
 
 fn mix(secret: u64, other: u64) -> u64 {
@@ -9,58 +11,183 @@ fn prune(secret: u64) -> u64 {
 }
 
 fn generate(x: u64) -> u64 {
-    let x2 = prune(mix(x, x * 64));
+    // Pruning keeps only the low bits, which wrapping leaves alone
+    let x2 = prune(mix(x, x.wrapping_mul(64)));
     let x3 = prune(mix(x2, x2 / 32));
-    let x4 = prune(mix(x3, x3 * 2048));
-    return x4;
+    prune(mix(x3, x3 * 2048))
 }
 
-pub fn solve(input: &str) -> Option<(u64, i32)> {
-    let mut part1 = 0;
-    let mut global: HashMap<_, i32> = HashMap::new();
-    for line in input.lines() {
-        let num = line.parse().ok()?;
-        let nums: Vec<_> = (0..2000)
-            .scan(num, |acc, _| {
-                let outcome = generate(*acc);
-                *acc = outcome;
-                Some(outcome)
-            })
-            .collect();
-
-        let mut local = HashMap::new();
-        for window in nums.windows(5) {
-            let mut diffs = [0; 4];
-            let mut idx = 0;
-            let mut value = 0;
-            // This is somewhat inefficient because it recomputes the diffs unnecessarily
-            for (prev, next) in window.iter().copied().zip(window.iter().copied().skip(1)) {
-                let prev = (prev % 10) as i32;
-                let next = (next % 10) as i32;
-                let diff = next - prev;
-                diffs[idx] = diff;
-                idx += 1;
-                value = next;
-            }
-            assert_eq!(idx, 4);
-            local.entry(diffs).or_insert(value);
+const NUM_SECRETS: usize = 2000;
+
+fn parse(input: &str) -> Result<Vec<u64>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(line_idx, line)| parse_token(line_idx, line, line))
+        .collect()
+}
+
+/// The next 2000 secret numbers of a buyer
+fn secrets(num: u64) -> Vec<u64> {
+    (0..NUM_SECRETS)
+        .scan(num, |acc, _| {
+            let outcome = generate(*acc);
+            *acc = outcome;
+            Some(outcome)
+        })
+        .collect()
+}
+
+/// Add the first price seen after each sequence of four changes into the global totals
+fn record_prices(nums: &[u64], global: &mut HashMap<[i32; 4], i32>) {
+    let mut local = HashMap::new();
+    for window in nums.windows(5) {
+        let mut diffs = [0; 4];
+        let mut idx = 0;
+        let mut value = 0;
+        // This is somewhat inefficient because it recomputes the diffs unnecessarily
+        for (prev, next) in window.iter().copied().zip(window.iter().copied().skip(1)) {
+            let prev = (prev % 10) as i32;
+            let next = (next % 10) as i32;
+            let diff = next - prev;
+            diffs[idx] = diff;
+            idx += 1;
+            value = next;
         }
-        // We just need to sum the contributions of all the local hashmaps
-        for (k, v) in local {
-            *global.entry(k).or_default() += v;
+        assert_eq!(idx, 4);
+        local.entry(diffs).or_insert(value);
+    }
+    // We just need to sum the contributions of all the local hashmaps
+    merge_prices(global, local);
+}
+
+fn merge_prices(global: &mut HashMap<[i32; 4], i32>, local: HashMap<[i32; 4], i32>) {
+    for (k, v) in local {
+        *global.entry(k).or_default() += v;
+    }
+}
+
+/// Total of each buyer's 2000th secret
+fn sum_secrets(nums: &[u64]) -> u64 {
+    let last = parallel::map(nums, |&num| secrets(num)[NUM_SECRETS - 1]);
+    last.into_iter().sum()
+}
+
+/// Price totals over every buyer, see [`record_prices`]
+fn all_prices(nums: &[u64]) -> HashMap<[i32; 4], i32> {
+    let partials = parallel::map_chunks(nums, |chunk| {
+        let mut partial = HashMap::new();
+        for &num in chunk {
+            record_prices(&secrets(num), &mut partial);
+        }
+        partial
+    });
+    let mut global = HashMap::new();
+    for partial in partials {
+        merge_prices(&mut global, partial);
+    }
+    global
+}
+
+fn best_price(global: &HashMap<[i32; 4], i32>) -> Result<i32> {
+    let best = global.values().max().copied();
+    best.ok_or_else(|| Error::no_solution("there are no buyers"))
+}
+
+pub fn part1(input: &str) -> Result<u64> {
+    Ok(sum_secrets(&parse(input)?))
+}
+
+pub fn part2(input: &str) -> Result<i32> {
+    best_price(&all_prices(&parse(input)?))
+}
+
+pub fn solve(input: &str) -> Result<(u64, i32)> {
+    let nums = parse(input)?;
+    let partials = parallel::map_chunks(&nums, |chunk| {
+        let mut part1 = 0;
+        let mut partial = HashMap::new();
+        for &num in chunk {
+            let secrets = secrets(num);
+            record_prices(&secrets, &mut partial);
+            part1 += secrets[NUM_SECRETS - 1];
         }
-        part1 += nums.last()?;
+        (part1, partial)
+    });
+    let mut part1 = 0;
+    let mut global = HashMap::new();
+    for (partial1, partial) in partials {
+        part1 += partial1;
+        merge_prices(&mut global, partial);
+    }
+    let part2 = best_price(&global)?;
+    Ok((part1, part2))
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    fn day(&self) -> u8 {
+        22
+    }
+    fn title(&self) -> &'static str {
+        "Monkey Market"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |nums| sum_secrets(&nums),
+            |nums| best_price(&all_prices(&nums)),
+        )
     }
-    let part2 = *global.values().max()?;
-    Some((part1, part2))
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p22.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+1
+10
+100
+2024
+";
+    const EXAMPLE_PART2: &str = "\
+1
+2
+3
+2024
+";
+
+    #[test]
+    fn day22_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 37327623);
+        assert_eq!(super::part2(EXAMPLE_PART2).unwrap(), 23);
+    }
+
     #[test]
     fn day22_solve() {
-        // crate::simple_bench(INPUT, super::solve);
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(22) else {
+            return;
+        };
+        crate::answers::assert_recorded(22, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day22_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
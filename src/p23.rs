@@ -1,18 +1,19 @@
 use std::collections::{HashMap, HashSet};
 
+use crate::{answer, answers, Answer, Error, Result, Solution, Timings};
 use itertools::Itertools;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 struct Graph {
     data: HashMap<CallSign, HashSet<CallSign>>,
 }
 
 impl Graph {
-    fn get_full_subgraph(&self) -> SubGraph {
-        SubGraph::new(&self, self.data.keys().copied().collect())
+    fn get_full_subgraph(&self) -> SubGraph<'_> {
+        SubGraph::new(self, self.data.keys().copied().collect())
     }
-    fn get_empty_subgraph(&self) -> SubGraph {
-        SubGraph::new(&self, Default::default())
+    fn get_empty_subgraph(&self) -> SubGraph<'_> {
+        SubGraph::new(self, Default::default())
     }
     fn neighbors(&self, vertex: CallSign) -> &HashSet<CallSign> {
         self.data.get(&vertex).unwrap()
@@ -81,10 +82,13 @@ struct CallSign {
 }
 
 impl CallSign {
-    fn new(input: &[u8]) -> Self {
-        let mut data = [0; 2];
-        data.copy_from_slice(input);
-        Self { data }
+    fn new(input: &[u8]) -> Option<Self> {
+        match *input {
+            [a, b] if a.is_ascii_lowercase() && b.is_ascii_lowercase() => {
+                Some(Self { data: [a, b] })
+            }
+            _ => None,
+        }
     }
     fn t_start(&self) -> bool {
         self.data[0] == b't'
@@ -118,8 +122,8 @@ fn maximal_clique<'a>(
         let v_neighbors = parent_graph.neighbors(v);
         let best = maximal_clique(
             r.vertex_union(v),
-            p.subset_intersection(&v_neighbors),
-            x.subset_intersection(&v_neighbors),
+            p.subset_intersection(v_neighbors),
+            x.subset_intersection(v_neighbors),
         );
         if let Some(best) = best {
             if best.len() as i32 > out_score {
@@ -156,37 +160,140 @@ fn three_cliques(k: CallSign, graph: &Graph) -> u64 {
     sum
 }
 
-pub fn solve(input: &str) -> Option<(u64, String)> {
+fn parse(input: &str) -> Result<Graph> {
     let mut graph: Graph = Default::default();
-    for line in input.lines() {
+    for (line_idx, line) in input.lines().enumerate() {
         let (left, right) = line
             .split("-")
             .map(|x| CallSign::new(x.as_bytes()))
-            .collect_tuple()?;
+            .collect_tuple()
+            .and_then(|(left, right)| Some((left?, right?)))
+            .ok_or_else(|| Error::parse("expected a link like kh-tc").at_line(line_idx))?;
         graph.data.entry(left).or_default().insert(right);
         graph.data.entry(right).or_default().insert(left);
     }
+    Ok(graph)
+}
+
+fn count_t_triangles(graph: &Graph) -> u64 {
     let mut part1 = 0;
     for k in graph.data.keys() {
         if k.t_start() {
-            part1 += three_cliques(*k, &graph);
+            part1 += three_cliques(*k, graph);
         }
     }
+    part1
+}
+
+fn password(graph: &Graph) -> Result<String> {
     let clique = maximal_clique(
         graph.get_empty_subgraph(),
         graph.get_full_subgraph(),
         graph.get_empty_subgraph(),
-    )?;
+    )
+    .ok_or_else(|| Error::no_solution("the network has no LAN party"))?;
+    Ok(clique.build_str())
+}
 
-    Some((part1, clique.build_str()))
+pub fn part1(input: &str) -> Result<u64> {
+    Ok(count_t_triangles(&parse(input)?))
+}
+
+pub fn part2(input: &str) -> Result<String> {
+    password(&parse(input)?)
+}
+
+pub fn solve(input: &str) -> Result<(u64, String)> {
+    let graph = parse(input)?;
+    Ok((count_t_triangles(&graph), password(&graph)?))
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    fn day(&self) -> u8 {
+        23
+    }
+    fn title(&self) -> &'static str {
+        "LAN Party"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |graph| count_t_triangles(&graph),
+            |graph| password(&graph),
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p23.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn
+";
+
+    #[test]
+    fn day23_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 7);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), "co,de,ka,ta");
+    }
+
     #[test]
     fn day23_solve() {
-        // crate::simple_bench(INPUT, super::solve);
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(23) else {
+            return;
+        };
+        crate::answers::assert_recorded(23, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day23_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,8 +1,9 @@
 use std::ops::Range;
 
+use crate::{answer, answers, Answer, Error, Result, Solution, Timings};
 use regex::Regex;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Command {
     location: usize,
     do_command: bool,
@@ -17,7 +18,7 @@ impl Command {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Commands {
     data: Vec<Command>,
     ptr: usize,
@@ -34,8 +35,9 @@ impl Commands {
         st..end
     }
     fn should_compute(&mut self, start: usize) -> bool {
-        if start < self.data[0].location {
-            return true;
+        match self.data.first() {
+            Some(first) if start >= first.location => {}
+            _ => return true, // Enabled until the first command
         }
         let mut range = self.get_range();
         while !range.contains(&start) {
@@ -46,11 +48,30 @@ impl Commands {
     }
 }
 
-pub fn solve(input: &str) -> Option<(i32, i32)> {
+#[derive(Clone)]
+struct Mul {
+    location: usize,
+    product: i32,
+}
+
+fn parse_muls(input: &str) -> Vec<Mul> {
+    // The operands are 1-3 digit numbers, so the parse cannot fail
+    let re = Regex::new(r"mul\(([0-9]{1,3}),([0-9]{1,3})\)").unwrap();
+    re.captures_iter(input)
+        .map(|val| {
+            let product = val
+                .iter()
+                .skip(1)
+                .map(|x| x.unwrap().as_str().parse::<i32>().unwrap())
+                .product();
+            let location = val.get(0).unwrap().start();
+            Mul { location, product }
+        })
+        .collect()
+}
+
+fn parse_commands(input: &str) -> Commands {
     let switch = Regex::new(r"do\(\)|don't\(\)").unwrap();
-    let re = Regex::new(r"mul\(([0-9]+),([0-9]+)\)").unwrap();
-    let mut part1 = 0;
-    let mut part2 = 0;
     let commands = switch
         .captures_iter(input)
         .map(|x| {
@@ -58,30 +79,113 @@ pub fn solve(input: &str) -> Option<(i32, i32)> {
             Command::new(m.end(), m.as_str() == "do()")
         })
         .collect();
-    let mut commands = Commands {
+    Commands {
         data: commands,
         ptr: 0,
-    };
-    for val in re.captures_iter(input) {
-        let prod: i32 = val
-            .iter()
-            .skip(1)
-            .map(|x| x.unwrap().as_str().parse::<i32>().unwrap())
-            .product();
-        let loc = val.get(0).unwrap().start();
-        part1 += prod;
-        if commands.should_compute(loc) {
-            part2 += prod;
+    }
+}
+
+fn add(total: i64, product: i32) -> Result<i64> {
+    total
+        .checked_add(product.into())
+        .ok_or_else(|| Error::no_solution("the sum of the products does not fit in 64 bits"))
+}
+
+fn sum<'a>(muls: impl IntoIterator<Item = &'a Mul>) -> Result<i64> {
+    muls.into_iter()
+        .try_fold(0, |total, mul| add(total, mul.product))
+}
+
+pub fn part1(input: &str) -> Result<i64> {
+    sum(&parse_muls(input))
+}
+
+pub fn part2(input: &str) -> Result<i64> {
+    let mut commands = parse_commands(input);
+    sum(parse_muls(input)
+        .iter()
+        .filter(|mul| commands.should_compute(mul.location)))
+}
+
+pub fn solve(input: &str) -> Result<(i64, i64)> {
+    let mut commands = parse_commands(input);
+    let mut part1 = 0;
+    let mut part2 = 0;
+    for mul in parse_muls(input) {
+        part1 = add(part1, mul.product)?;
+        if commands.should_compute(mul.location) {
+            part2 = add(part2, mul.product)?;
         }
     }
-    Some((part1, part2))
+    Ok((part1, part2))
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    fn day(&self) -> u8 {
+        3
+    }
+    fn title(&self) -> &'static str {
+        "Mull It Over"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            |input| Ok((parse_muls(input), parse_commands(input))),
+            |(muls, _)| sum(&muls),
+            |(muls, mut commands)| {
+                sum(muls
+                    .iter()
+                    .filter(|mul| commands.should_compute(mul.location)))
+            },
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p3.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
+    const EXAMPLE_PART2: &str =
+        "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    #[test]
+    fn day3_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 161);
+        assert_eq!(super::part2(EXAMPLE_PART2).unwrap(), 48);
+        assert_eq!(super::solve(EXAMPLE_PART2).unwrap(), (161, 48));
+    }
+
     #[test]
     fn day3_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(3) else {
+            return;
+        };
+        crate::answers::assert_recorded(3, super::solve(&input).unwrap());
+    }
+
+    #[test]
+    fn day3_large_sum() {
+        let input = "mul(999,999)".repeat(3000);
+        assert_eq!(super::solve(&input).unwrap(), (2994003000, 2994003000));
+    }
+
+    proptest! {
+        #[test]
+        fn day3_never_panics(input in crate::fuzz::mutations(EXAMPLE_PART2)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,102 +1,157 @@
-struct SquareGrid {
-    data: Vec<char>,
-    length: usize,
-}
+use crate::{answer, answers, Answer, Direction, Error, Grid, Result, Solution, Timings};
 
-impl SquareGrid {
-    fn new(data: Vec<char>, length: usize) -> Self {
-        Self { data, length }
-    }
-    fn cross(&self, row: usize, col: usize) -> bool {
-        if self.get(row, col).unwrap() != 'A' {
-            return false;
-        }
-        let c1 = &[(row - 1, col - 1), (row, col), (row + 1, col + 1)];
-        let c2 = &[(row - 1, col + 1), (row, col), (row + 1, col - 1)];
-        let mut count = 0;
-        for word in [self.build_string(c1), self.build_string(c2)] {
-            if let Some(word) = word {
-                if word == "MAS" || word == "SAM" {
-                    count += 1;
-                }
+fn count_xmas(grid: &Grid<char>) -> i32 {
+    let mut count = 0;
+    for (position, _) in grid.iter().filter(|(_, c)| **c == 'X') {
+        for direction in Direction::ALL {
+            if spells(grid, position, direction, "XMAS") {
+                count += 1;
             }
         }
-        count == 2
     }
-    fn hits(&self, row: usize, col: usize) -> i32 {
-        let root = self.get(row, col).unwrap();
-        if (root != 'S') && (root != 'X') {
-            return 0;
-        }
-        let diag = &[
-            (row, col),
-            (row + 1, col + 1),
-            (row + 2, col + 2),
-            (row + 3, col + 3),
-        ];
-        let diag2 = &[
-            (row, col),
-            (row + 1, col - 1),
-            (row + 2, col - 2),
-            (row + 3, col - 3),
-        ];
-        let vert = &[(row, col), (row + 1, col), (row + 2, col), (row + 3, col)];
-        let hor = &[(row, col), (row, col + 1), (row, col + 2), (row, col + 3)];
-        let words = [
-            self.build_string(diag),
-            self.build_string(diag2),
-            self.build_string(hor),
-            self.build_string(vert),
-        ];
-        let mut hits = 0;
-        for word in words {
-            if let Some(word) = word {
-                let word = word.as_str();
-                if word == "XMAS" || word == "SAMX" {
-                    hits += 1;
-                }
-            }
-        }
-        hits
+    count
+}
+
+fn count_crosses(grid: &Grid<char>) -> i32 {
+    grid.positions()
+        .filter(|&position| cross(grid, position))
+        .count() as i32
+}
+
+/// Both diagonals through an `A` read MAS, in either direction
+fn cross(grid: &Grid<char>, position: (usize, usize)) -> bool {
+    if grid[position] != 'A' {
+        return false;
     }
-    fn build_string(&self, arr: &[(usize, usize)]) -> Option<String> {
-        arr.iter().map(|&(r, c)| self.get(r, c)).collect()
+    // MAS starting in the corner the direction points at, read back through the centre
+    let from_corner = |direction: Direction| {
+        grid.step(position, direction)
+            .is_some_and(|corner| spells(grid, corner, direction.reverse(), "MAS"))
+    };
+    (from_corner(Direction::NorthWest) || from_corner(Direction::SouthEast))
+        && (from_corner(Direction::NorthEast) || from_corner(Direction::SouthWest))
+}
+
+/// Whether `word` is read by starting at `position` and stepping in `direction`
+fn spells(grid: &Grid<char>, position: (usize, usize), direction: Direction, word: &str) -> bool {
+    let mut current = Some(position);
+    for letter in word.chars() {
+        let Some(position) = current.filter(|&position| grid[position] == letter) else {
+            return false;
+        };
+        current = grid.step(position, direction);
     }
-    fn get(&self, row: usize, col: usize) -> Option<char> {
-        if row >= self.length || col >= self.length {
-            None
+    true
+}
+
+fn parse(input: &str) -> Result<Grid<char>> {
+    let grid = Grid::parse(input, |_, c| {
+        if c.is_ascii_alphabetic() {
+            Ok(c)
         } else {
-            Some(self.data[row * self.length + col])
+            Err(Error::unexpected_char(c))
         }
-    }
+    })?;
+    Ok(grid)
 }
 
-pub fn solve(input: &str) -> Option<(i32, i32)> {
-    let length = input
-        .lines()
-        .nth(0)?
-        .chars()
-        .filter(|x| x.is_ascii_alphabetic())
-        .count();
-    let data = input.chars().filter(|x| x.is_ascii_alphabetic()).collect();
-    let grid = SquareGrid::new(data, length);
-    assert_eq!(length * length, grid.data.len());
-    let mut part1 = 0;
-    let mut part2 = 0;
-    for i in 0..length {
-        for j in 0..length {
-            part1 += grid.hits(i, j);
-            part2 += grid.cross(i, j) as i32;
-        }
+pub fn part1(input: &str) -> Result<i32> {
+    Ok(count_xmas(&parse(input)?))
+}
+
+pub fn part2(input: &str) -> Result<i32> {
+    Ok(count_crosses(&parse(input)?))
+}
+
+pub fn solve(input: &str) -> Result<(i32, i32)> {
+    let grid = parse(input)?;
+    Ok((count_xmas(&grid), count_crosses(&grid)))
+}
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    fn day(&self) -> u8 {
+        4
+    }
+    fn title(&self) -> &'static str {
+        "Ceres Search"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |grid| count_xmas(&grid),
+            |grid| count_crosses(&grid),
+        )
     }
-    Some((part1, part2))
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p4.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX
+";
+
+    const EXAMPLE_WIDE: &str = "\
+MMMSXXMASMZZZ
+MSAMXMSMSAZZZ
+AMXSXMAAMMZZZ
+MSAMASMSMXZZZ
+XMASAMXAMMZZZ
+XXAMMXXAMAZZZ
+SMSMSASXSSZZZ
+SAXAMASAAAZZZ
+MAMMMXMMMMZZZ
+MXMXAXMASXZZZ
+";
+
+    #[test]
+    fn day4_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 18);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 9);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (18, 9));
+    }
+
+    #[test]
+    fn day4_rectangular() {
+        // Padding with letters that spell nothing keeps the counts
+        assert_eq!(super::solve(EXAMPLE_WIDE).unwrap(), (18, 9));
+    }
+
     #[test]
     fn day4_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(4) else {
+            return;
+        };
+        crate::answers::assert_recorded(4, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day4_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,3 +1,5 @@
+use crate::error::parse_token;
+use crate::{answer, answers, Answer, Error, Result, Solution, Timings};
 use itertools::Itertools;
 
 #[derive(Debug, Clone, Copy)]
@@ -13,59 +15,100 @@ impl Edge {
     }
 }
 
-pub fn solve(input: &str) -> Option<(usize, usize)> {
-    let mut part1 = 0;
-    let mut part2 = 0;
-    let mut max_edge = 0;
-    let rules: Vec<_> = input
-        .lines()
-        .filter_map(|x| {
-            let data = x.split("|").map(|x| x.parse().ok()).collect_tuple();
-            if let Some((Some(src), Some(dest))) = data {
-                max_edge = std::cmp::max(max_edge, src);
-                max_edge = std::cmp::max(max_edge, dest);
-                Some(Edge { src, dest })
-            } else {
-                None
-            }
-        })
-        .collect();
-    // Adjacency List
-    let mut edge_rules = vec![Vec::new(); max_edge + 1];
-    for rule in rules {
-        edge_rules[rule.src].push(rule);
-    }
-    let lists: Vec<Vec<_>> = input
-        .lines()
-        .filter_map(|x| {
-            if x.contains(",") {
-                let vec = x.split(",").map(|x| x.parse::<usize>().ok()).collect();
-                vec
-            } else {
-                None
-            }
-        })
-        .collect();
-    for list in lists.iter() {
-        // Filter out only rules where both src and dest appear in our list
+#[derive(Clone)]
+struct Manual {
+    /// Adjacency list of ordering rules, indexed by source page
+    edge_rules: Vec<Vec<Edge>>,
+    lists: Vec<Vec<usize>>,
+}
+
+impl Manual {
+    /// Filter out only rules where both src and dest appear in our list
+    fn relevant_rules(&self, list: &[usize]) -> Vec<&Edge> {
         let mut relevant_rules = Vec::new();
         for val in list {
-            for rule in edge_rules[*val as usize].iter() {
+            for rule in self.edge_rules.get(*val).into_iter().flatten() {
                 if list.contains(&rule.dest) {
                     relevant_rules.push(rule);
                 }
             }
         }
-        if relevant_rules.iter().all(|x| x.is_obeyed(&list)) {
-            part1 += list[list.len() / 2];
-        } else {
-            part2 += topsort_middle(&mut relevant_rules, &list);
+        relevant_rules
+    }
+    fn ordered_middles(&self) -> usize {
+        let mut out = 0;
+        for list in self.lists.iter() {
+            let relevant_rules = self.relevant_rules(list);
+            if relevant_rules.iter().all(|x| x.is_obeyed(list)) {
+                out += list[list.len() / 2];
+            }
+        }
+        out
+    }
+    fn reordered_middles(&self) -> Result<usize> {
+        let mut out = 0;
+        for list in self.lists.iter() {
+            let mut relevant_rules = self.relevant_rules(list);
+            if !relevant_rules.iter().all(|x| x.is_obeyed(list)) {
+                out += topsort_middle(&mut relevant_rules, list)?;
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Pages have two digits, which also keeps the adjacency list of rules small
+fn parse_page(line_idx: usize, line: &str, page: &str) -> Result<usize> {
+    parse_token::<u8>(line_idx, line, page).map(usize::from)
+}
+
+fn parse(input: &str) -> Result<Manual> {
+    let mut max_edge = 0;
+    let mut rules = Vec::new();
+    let mut lines = input.lines().enumerate();
+    for (line_idx, line) in lines.by_ref() {
+        if line.is_empty() {
+            break; // Page updates follow the rules
         }
+        let (src, dest) = line
+            .split_once('|')
+            .ok_or_else(|| Error::parse("expected a rule like 47|53").at_line(line_idx))?;
+        let src = parse_page(line_idx, line, src)?;
+        let dest = parse_page(line_idx, line, dest)?;
+        max_edge = std::cmp::max(max_edge, src);
+        max_edge = std::cmp::max(max_edge, dest);
+        rules.push(Edge { src, dest });
     }
-    Some((part1, part2))
+    // Adjacency List
+    let mut edge_rules = vec![Vec::new(); max_edge + 1];
+    for rule in rules {
+        edge_rules[rule.src].push(rule);
+    }
+    let mut lists = Vec::new();
+    for (line_idx, line) in lines.filter(|(_, line)| !line.is_empty()) {
+        let list = line
+            .split(',')
+            .map(|page| parse_page(line_idx, line, page))
+            .collect::<Result<Vec<usize>>>()?;
+        lists.push(list);
+    }
+    Ok(Manual { edge_rules, lists })
+}
+
+pub fn part1(input: &str) -> Result<usize> {
+    Ok(parse(input)?.ordered_middles())
+}
+
+pub fn part2(input: &str) -> Result<usize> {
+    parse(input)?.reordered_middles()
 }
 
-fn topsort_middle(relevant_rules: &mut Vec<&Edge>, list: &[usize]) -> usize {
+pub fn solve(input: &str) -> Result<(usize, usize)> {
+    let manual = parse(input)?;
+    Ok((manual.ordered_middles(), manual.reordered_middles()?))
+}
+
+fn topsort_middle(relevant_rules: &mut Vec<&Edge>, list: &[usize]) -> Result<usize> {
     // Kahn's Algorithm, cutoff halfway through
     let is_root = |rules: &[&Edge], x| rules.iter().find(|y| y.dest == x).is_none();
     let mut out = Vec::new();
@@ -73,7 +116,10 @@ fn topsort_middle(relevant_rules: &mut Vec<&Edge>, list: &[usize]) -> usize {
         .iter()
         .filter(|&&x| is_root(relevant_rules, x))
         .collect_vec();
-    assert!(start.len() * 2 < list.len()); // Middle would be non-deterministic
+    let ambiguous = || Error::no_solution("the rules do not fix the middle page of an update");
+    if start.len() * 2 >= list.len() {
+        return Err(ambiguous()); // Middle would be non-deterministic
+    }
     let target = list.len() / 2;
     while let Some(node) = start.pop() {
         out.push(*node);
@@ -88,14 +134,145 @@ fn topsort_middle(relevant_rules: &mut Vec<&Edge>, list: &[usize]) -> usize {
             }
         }
     }
-    *out.last().unwrap()
+    out.last().copied().ok_or_else(ambiguous)
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    fn day(&self) -> u8 {
+        5
+    }
+    fn title(&self) -> &'static str {
+        "Print Queue"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |manual| manual.ordered_middles(),
+            |manual| manual.reordered_middles(),
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p5.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47
+";
+
+    #[test]
+    fn day5_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 143);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 123);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (143, 123));
+    }
+
     #[test]
     fn day5_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(5) else {
+            return;
+        };
+        crate::answers::assert_recorded(5, super::solve(&input).unwrap());
+    }
+
+    /// Sort each update by the rules, which give a total order, and sum the middle pages
+    fn naive(order: &[usize], updates: &[Vec<usize>]) -> (usize, usize) {
+        let (mut ordered, mut reordered) = (0, 0);
+        for update in updates {
+            let mut sorted = update.clone();
+            sorted.sort_by_key(|page| order.iter().position(|x| x == page));
+            if sorted == *update {
+                ordered += update[update.len() / 2];
+            } else {
+                reordered += sorted[sorted.len() / 2];
+            }
+        }
+        (ordered, reordered)
+    }
+
+    /// Pages in the order the rules put them, and updates of an odd number of those pages
+    fn manual() -> impl Strategy<Value = (Vec<usize>, Vec<Vec<usize>>)> {
+        prop::sample::subsequence((10..100).collect::<Vec<usize>>(), 1..12)
+            .prop_shuffle()
+            .prop_flat_map(|order| {
+                let len = order.len();
+                let update = prop::sample::subsequence(order.clone(), 1..=len)
+                    .prop_map(|mut pages| {
+                        // Puzzle updates always have a middle page
+                        if pages.len().is_multiple_of(2) {
+                            pages.pop();
+                        }
+                        pages
+                    })
+                    .prop_shuffle();
+                (Just(order), prop::collection::vec(update, 1..8))
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn day5_matches_naive((order, updates) in manual()) {
+            let mut input = String::new();
+            for (idx, src) in order.iter().enumerate() {
+                for dest in &order[idx + 1..] {
+                    input.push_str(&format!("{}|{}\n", src, dest));
+                }
+            }
+            input.push('\n');
+            for update in &updates {
+                let pages: Vec<_> = update.iter().map(usize::to_string).collect();
+                input.push_str(&pages.join(","));
+                input.push('\n');
+            }
+            prop_assert_eq!(super::solve(&input).unwrap(), naive(&order, &updates));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn day5_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,4 +1,5 @@
-use super::Direction;
+use super::visual::{Animate, Animation, Cell, Color, Frame};
+use super::{answer, answers, parallel, Answer, Direction, Error, Grid, Result, Solution, Timings};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Marker {
@@ -13,29 +14,25 @@ enum Status {
     Cycle,
 }
 
-struct SquareGrid {
-    data: Vec<Marker>,
-    length: usize,
+#[derive(Clone)]
+struct Lab {
+    map: Grid<Marker>,
     start: (usize, usize),
     position: (usize, usize),
     start_direction: Direction,
     direction: Direction,
     history: Vec<usize>,
 }
-impl SquareGrid {
+impl Lab {
     fn step(&mut self) -> Status {
-        let (row, col) = self.position;
-        let location = match self.direction {
-            Direction::North => (row - 1, col),
-            Direction::East => (row, col + 1),
-            Direction::South => (row + 1, col),
-            Direction::West => (row, col - 1),
+        let Some(location) = self.map.step(self.position, self.direction) else {
+            return Status::OutofBounds;
         };
-        if let Some(val) = self.get_mut(location.0, location.1) {
-            if let Marker::Obstacle = val {
+        match self.map[location] {
+            Marker::Obstacle => {
                 // Turn, do not move
                 self.direction = self.direction.turn();
-                self.history.push(location.0 * self.length + location.1);
+                self.history.push(self.map.index_of(location));
                 let hist_len = self.history.len();
                 if hist_len > 10 {
                     // Check for cycles. See if the same two consecutive obstacle turns are observed twice
@@ -52,20 +49,13 @@ impl SquareGrid {
                         }
                     }
                 }
-            } else {
-                *val = Marker::Visited;
+                Status::Running
+            }
+            _ => {
+                self.map[location] = Marker::Visited;
                 self.position = location;
+                Status::Running
             }
-            Status::Running
-        } else {
-            Status::OutofBounds
-        }
-    }
-    fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut Marker> {
-        if row >= self.length || col >= self.length {
-            None
-        } else {
-            Some(&mut self.data[row * self.length + col])
         }
     }
     fn reset(&mut self) {
@@ -73,75 +63,206 @@ impl SquareGrid {
         self.direction = self.start_direction;
         self.history.clear();
     }
+    /// Walk the guard out of the grid, returning the visited squares other than the start
+    fn patrol(&mut self) -> Vec<(usize, usize)> {
+        while let Status::Running = self.step() {}
+        self.map
+            .iter()
+            .filter(|&(position, x)| (*x == Marker::Visited) && (position != self.start))
+            .map(|(position, _)| position)
+            .collect()
+    }
+    fn count_loops(&self, marked: Vec<(usize, usize)>) -> usize {
+        // Only need to check marked squares, otherwise the extra obstacle will never be encountered
+        let counts = parallel::map_chunks(&marked, |chunk| {
+            let mut lab = self.clone();
+            chunk
+                .iter()
+                .filter(|&&position| lab.loops_with(position))
+                .count()
+        });
+        counts.into_iter().sum()
+    }
+    /// Whether an extra obstacle at `position` traps the guard in a loop
+    fn loops_with(&mut self, position: (usize, usize)) -> bool {
+        self.reset();
+        self.map[position] = Marker::Obstacle;
+        let mut status = Status::Running;
+        while let Status::Running = status {
+            status = self.step();
+        }
+        self.map[position] = Marker::Visited;
+        matches!(status, Status::Cycle)
+    }
+    fn frame(&self, steps: usize) -> Frame {
+        let mut visited = 0;
+        let mut frame = Frame::new(&self.map, |marker| match marker {
+            Marker::Empty => Cell::plain('.'),
+            Marker::Obstacle => Cell::new('#', Color::Gray),
+            Marker::Visited => {
+                visited += 1;
+                Cell::new('X', Color::Yellow)
+            }
+        });
+        let guard = match self.direction {
+            Direction::North => '^',
+            Direction::East => '>',
+            Direction::South => 'v',
+            _ => '<',
+        };
+        frame.set(self.position, Cell::new(guard, Color::Red));
+        frame.caption(format!("step {}, {} squares visited", steps, visited))
+    }
 }
 
-pub fn solve(input: &str) -> Option<(usize, usize)> {
-    let mut part2 = 0;
+fn parse(input: &str) -> Result<Lab> {
     let mut direction = None;
-    let mut data = Vec::new();
-    let mut counter = 0;
     let mut start = (0, 0);
-    for (row, line) in input.lines().enumerate() {
-        counter = 0;
-        for (col, c) in line.chars().enumerate() {
-            counter += 1;
-            let mut value = Marker::Empty;
-            match c {
-                '.' => {}
-                '#' => value = Marker::Obstacle,
-                '^' => {
-                    value = Marker::Visited;
-                    direction = Some(Direction::North);
-                    start = (row, col);
-                }
-                _ => unimplemented!(), // Seems it always starts north?
-            };
-            data.push(value);
+    let map = Grid::parse(input, |position, c| match c {
+        '.' => Ok(Marker::Empty),
+        '#' => Ok(Marker::Obstacle),
+        '^' | '>' | 'v' | '<' => {
+            if direction.is_some() {
+                return Err(Error::parse("found a second guard"));
+            }
+            direction = Some(Direction::try_from(c)?);
+            start = position;
+            Ok(Marker::Visited)
         }
-    }
-    let direction = direction?;
-    assert_eq!(counter * counter, data.len()); // Grid is square
-    let mut grid = SquareGrid {
-        data,
-        length: counter,
+        _ => Err(Error::unexpected_char(c)),
+    })?;
+    let direction = direction.ok_or_else(|| Error::parse("no guard on the map"))?;
+    Ok(Lab {
+        map,
         position: start,
         start,
         direction,
         start_direction: direction,
         history: Vec::new(),
-    };
-    while let Status::Running = grid.step() {}
-    let start_idx = grid.start.0 * grid.length + grid.start.1;
-    let marked: Vec<_> = grid
-        .data
-        .iter()
-        .copied()
-        .enumerate()
-        .filter(|(idx, x)| (*x == Marker::Visited) && (*idx != start_idx))
-        .collect();
+    })
+}
+
+pub fn part1(input: &str) -> Result<usize> {
+    let marked = parse(input)?.patrol();
+    Ok(marked.len() + 1) // Marked + start position
+}
+
+pub fn part2(input: &str) -> Result<usize> {
+    let mut lab = parse(input)?;
+    let marked = lab.patrol();
+    Ok(lab.count_loops(marked))
+}
+
+pub fn solve(input: &str) -> Result<(usize, usize)> {
+    let mut lab = parse(input)?;
+    let marked = lab.patrol();
     let part1 = marked.len() + 1; // Marked + start position
+    let part2 = lab.count_loops(marked);
+    Ok((part1, part2))
+}
 
-    // Only need to check marked squares, otherwise the extra obstacle will never be encountered
-    for (idx, _) in marked.into_iter() {
-        grid.reset();
-        grid.data[idx] = Marker::Obstacle;
-        let mut status = Status::Running;
-        while let Status::Running = status {
-            status = grid.step();
-        }
-        if let Status::Cycle = status {
-            part2 += 1;
-        }
-        grid.data[idx] = Marker::Visited;
+/// Play the guard's patrol, one step per frame
+pub fn animate(input: &str, animation: &mut Animation) -> Result<()> {
+    let mut lab = parse(input)?;
+    let mut steps = 0;
+    while let Status::Running = lab.step() {
+        steps += 1;
+        animation.show(|| lab.frame(steps));
+    }
+    animation.show_always(lab.frame(steps));
+    Ok(())
+}
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    fn day(&self) -> u8 {
+        6
+    }
+    fn title(&self) -> &'static str {
+        "Guard Gallivant"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |mut lab| lab.patrol().len() + 1,
+            |mut lab| {
+                let marked = lab.patrol();
+                lab.count_loops(marked)
+            },
+        )
+    }
+    fn animate(&self) -> Option<Animate> {
+        Some(animate)
     }
-    Some((part1, part2))
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p6.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...
+";
+
+    const EXAMPLE_WIDE: &str = "\
+.....#.....
+..........#
+...........
+...#.......
+........#..
+...........
+..#..^.....
+.........#.
+.#.........
+.......#...
+";
+
+    #[test]
+    fn day6_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 41);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 6);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (41, 6));
+    }
+
+    #[test]
+    fn day6_rectangular() {
+        // The guard never reaches the extra column on the left
+        assert_eq!(super::solve(EXAMPLE_WIDE).unwrap(), (41, 6));
+    }
+
     #[test]
     fn day6_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(6) else {
+            return;
+        };
+        crate::answers::assert_recorded(6, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day6_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,17 +1,21 @@
-fn add(a: i64, b: i64) -> i64 {
-    a + b
+use crate::error::parse_token;
+use crate::{answer, answers, parallel, parse, Answer, Error, Result, Solution, Timings};
+
+// Operators give `None` on overflow, which no goal can be
+fn add(a: i64, b: i64) -> Option<i64> {
+    a.checked_add(b)
 }
 
-fn mul(a: i64, b: i64) -> i64 {
-    a * b
+fn mul(a: i64, b: i64) -> Option<i64> {
+    a.checked_mul(b)
 }
 
-fn cat(a: i64, b: i64) -> i64 {
-    let b_digits = b.ilog10() + 1;
-    a * 10i64.pow(b_digits) + b
+fn cat(a: i64, b: i64) -> Option<i64> {
+    let b_digits = b.checked_ilog10().unwrap_or(0) + 1;
+    a.checked_mul(10i64.checked_pow(b_digits)?)?.checked_add(b)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Equation {
     goal: i64,
     numbers: Vec<i64>,
@@ -25,7 +29,9 @@ impl Equation {
         }
         let next = self.numbers[idx];
         for op in [add, mul] {
-            let temp = op(acc, next);
+            let Some(temp) = op(acc, next) else {
+                continue;
+            };
             if self.evaluate(idx + 1, temp) {
                 return true;
             }
@@ -39,7 +45,9 @@ impl Equation {
         }
         let next = self.numbers[idx];
         for op in [cat, add, mul] {
-            let temp = op(acc, next);
+            let Some(temp) = op(acc, next) else {
+                continue;
+            };
             if self.evaluate2(idx + 1, temp) {
                 return true;
             }
@@ -48,34 +56,133 @@ impl Equation {
     }
 }
 
-pub fn solve(input: &str) -> Option<(i64, i64)> {
-    let equations = input.lines().map(|line| {
-        let mut split = line
-            .split(&[':', ' '])
-            .filter_map(|x| x.parse::<i64>().ok());
-        let goal = split.next().unwrap();
-        let numbers: Vec<_> = split.collect();
-        assert!(numbers.len() >= 2);
-        Equation { goal, numbers }
-    });
-    let mut part1 = 0;
-    let mut part2 = 0;
-    for eq in equations {
+fn parse(input: &str) -> Result<Vec<Equation>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(line_idx, line)| {
+            let (goal, numbers) = line
+                .split_once(':')
+                .ok_or_else(|| Error::parse("expected a test value and ':'").at_line(line_idx))?;
+            let goal = parse_token(line_idx, line, goal)?;
+            let numbers = parse::words(line_idx, line, numbers)?;
+            if numbers.is_empty() {
+                return Err(Error::parse("equation has no numbers").at_line(line_idx));
+            }
+            Ok(Equation { goal, numbers })
+        })
+        .collect()
+}
+
+fn checked_sum(goals: impl IntoIterator<Item = i64>) -> Result<i64> {
+    goals
+        .into_iter()
+        .try_fold(0i64, |total, goal| total.checked_add(goal))
+        .ok_or_else(|| Error::no_solution("the total calibration does not fit in 64 bits"))
+}
+
+/// Sum the goals of the equations that some choice of operators makes true
+fn total_calibration(equations: &[Equation], concatenate: bool) -> Result<i64> {
+    let solvable = |eq: &Equation| {
+        eq.evaluate(1, eq.numbers[0]) || (concatenate && eq.evaluate2(1, eq.numbers[0]))
+    };
+    checked_sum(parallel::map(equations, |eq| {
+        if solvable(eq) {
+            eq.goal
+        } else {
+            0
+        }
+    }))
+}
+
+pub fn part1(input: &str) -> Result<i64> {
+    total_calibration(&parse(input)?, false)
+}
+
+pub fn part2(input: &str) -> Result<i64> {
+    total_calibration(&parse(input)?, true)
+}
+
+pub fn solve(input: &str) -> Result<(i64, i64)> {
+    let equations = parse(input)?;
+    let outcomes = parallel::map(&equations, |eq| {
         if eq.evaluate(1, eq.numbers[0]) {
-            part1 += eq.goal;
+            (eq.goal, 0)
         } else if eq.evaluate2(1, eq.numbers[0]) {
-            part2 += eq.goal;
+            (0, eq.goal)
+        } else {
+            (0, 0)
         }
+    });
+    let part1 = checked_sum(outcomes.iter().map(|&(part1, _)| part1))?;
+    let part2 = checked_sum(outcomes.iter().flat_map(|&(part1, part2)| [part1, part2]))?;
+    Ok((part1, part2))
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    fn day(&self) -> u8 {
+        7
+    }
+    fn title(&self) -> &'static str {
+        "Bridge Repair"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |equations| total_calibration(&equations, false),
+            |equations| total_calibration(&equations, true),
+        )
     }
-    part2 += part1;
-    Some((part1, part2))
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p7.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+190: 10 19
+3267: 81 40 27
+83: 17 5
+156: 15 6
+7290: 6 8 6 15
+161011: 16 10 13
+192: 17 8 14
+21037: 9 7 18 13
+292: 11 6 16 20
+";
+
+    #[test]
+    fn day7_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 3749);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 11387);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (3749, 11387));
+    }
+
     #[test]
     fn day7_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(7) else {
+            return;
+        };
+        crate::answers::assert_recorded(7, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day7_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,99 +1,172 @@
+use crate::{answer, answers, Answer, Grid, Point, Result, Solution, Timings};
 use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
-struct Point2D {
-    row: usize,
-    col: usize,
+#[derive(Clone)]
+struct Antennas {
+    map: HashMap<char, Vec<Point>>,
+    num_rows: usize,
+    num_cols: usize,
 }
 
-impl Point2D {
-    fn new(row: usize, col: usize) -> Self {
-        Self { row, col }
-    }
-}
-
-impl std::ops::Add<Point2D> for Point2D {
-    type Output = Self;
-
-    fn add(self, rhs: Point2D) -> Self::Output {
-        Self {
-            row: self.row + rhs.row,
-            col: self.col + rhs.col,
-        }
-    }
-}
-
-impl std::ops::Sub<Point2D> for Point2D {
-    type Output = Self;
-
-    fn sub(self, rhs: Point2D) -> Self::Output {
-        Self {
-            row: self.row - rhs.row,
-            col: self.col - rhs.col,
-        }
-    }
-}
-
-pub fn solve(input: &str) -> Option<(usize, usize)> {
-    let mut counter = 0;
-    let mut num_rows = 0;
-    let mut map: HashMap<char, Vec<Point2D>> = HashMap::new();
-    let mut targets = HashSet::new();
-    let mut targets2 = HashSet::new();
-    for (row, line) in input.lines().enumerate() {
-        for (col, c) in line.chars().enumerate() {
-            counter += 1;
-            if c != '.' {
-                map.entry(c).or_default().push(Point2D::new(row, col));
-            }
-        }
-        num_rows += 1;
-    }
-    assert_eq!(num_rows * num_rows, counter); // Is square
-    let in_bounds = |dest: Point2D| dest.row < num_rows && dest.col < num_rows;
-    for (_k, vec) in map.iter().filter(|(_, vec)| vec.len() > 1) {
-        for (i, val1) in vec.iter().copied().enumerate() {
-            targets2.insert(val1);
-            for (j, val2) in vec.iter().copied().enumerate() {
-                if i == j {
-                    continue;
+impl Antennas {
+    /// Count antinodes, either only the nearest on each side or every resonant harmonic
+    fn count_antinodes(&self, resonant: bool) -> usize {
+        let bounds = (self.num_rows, self.num_cols);
+        let in_bounds = |dest: Point| dest.to_grid(bounds).is_some();
+        let mut targets = HashSet::new();
+        for (_k, vec) in self.map.iter().filter(|(_, vec)| vec.len() > 1) {
+            for (i, val1) in vec.iter().copied().enumerate() {
+                if resonant {
+                    targets.insert(val1);
                 }
-                let dist = val2 - val1;
-                let mut dest1 = val2 + dist;
-                let mut dest2 = val1 - dist;
-                // Direction 1
-                if in_bounds(dest1) {
-                    targets.insert(dest1);
-                    loop {
-                        targets2.insert(dest1);
-                        dest1 = dest1 + dist;
-                        if !in_bounds(dest1) {
+                for (j, val2) in vec.iter().copied().enumerate() {
+                    if i == j {
+                        continue;
+                    }
+                    let dist = val2 - val1;
+                    let mut dest1 = val2 + dist;
+                    let mut dest2 = val1 - dist;
+                    // Direction 1
+                    while in_bounds(dest1) {
+                        targets.insert(dest1);
+                        if !resonant {
                             break;
                         }
+                        dest1 += dist;
                     }
-                }
-                // Direction 2
-                if in_bounds(dest2) {
-                    targets.insert(dest2);
-                    loop {
-                        targets2.insert(dest2);
-                        dest2 = dest2 - dist;
-                        if !in_bounds(dest2) {
+                    // Direction 2
+                    while in_bounds(dest2) {
+                        targets.insert(dest2);
+                        if !resonant {
                             break;
                         }
+                        dest2 -= dist;
                     }
                 }
             }
         }
+        targets.len()
+    }
+}
+
+fn parse(input: &str) -> Result<Antennas> {
+    let mut map: HashMap<char, Vec<Point>> = HashMap::new();
+    let grid = Grid::parse(input, |(row, col), c| {
+        if c != '.' {
+            map.entry(c).or_default().push(Point::from_grid((row, col)));
+        }
+        Ok(c)
+    })?;
+    Ok(Antennas {
+        map,
+        num_rows: grid.num_rows(),
+        num_cols: grid.num_cols(),
+    })
+}
+
+pub fn part1(input: &str) -> Result<usize> {
+    Ok(parse(input)?.count_antinodes(false))
+}
+
+pub fn part2(input: &str) -> Result<usize> {
+    Ok(parse(input)?.count_antinodes(true))
+}
+
+pub fn solve(input: &str) -> Result<(usize, usize)> {
+    let antennas = parse(input)?;
+    Ok((
+        antennas.count_antinodes(false),
+        antennas.count_antinodes(true),
+    ))
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    fn day(&self) -> u8 {
+        8
+    }
+    fn title(&self) -> &'static str {
+        "Resonant Collinearity"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |antennas| antennas.count_antinodes(false),
+            |antennas| antennas.count_antinodes(true),
+        )
     }
-    Some((targets.len(), targets2.len()))
 }
 
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p8.txt");
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "\
+............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............
+";
+
+    const EXAMPLE_WIDE: &str = "\
+...............
+........0......
+.....0.........
+.......0.......
+....0..........
+......A........
+...............
+...............
+........A......
+.........A.....
+...............
+...............
+";
+
+    #[test]
+    fn day8_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 14);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 34);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (14, 34));
+    }
+
+    #[test]
+    fn day8_rectangular() {
+        assert_eq!(super::solve(EXAMPLE_WIDE).unwrap(), (14, 36));
+    }
+
     #[test]
     fn day8_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(8) else {
+            return;
+        };
+        crate::answers::assert_recorded(8, super::solve(&input).unwrap());
+    }
+
+    proptest! {
+        #[test]
+        fn day8_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
@@ -1,88 +1,118 @@
-use std::usize;
+use crate::{answer, answers, Answer, Error, Result, Solution, Timings};
 
 const EMPTY: usize = usize::MAX;
 
-fn get_number(val: u8) -> u8 {
-    return val - b'0';
+/// The digit at byte `idx` of `line`; every byte before it is a digit, so `idx` is also its column
+fn get_number(line: &str, idx: usize) -> Result<u8> {
+    let val = line.as_bytes()[idx];
+    if val.is_ascii_digit() {
+        Ok(val - b'0')
+    } else {
+        let c = line[idx..].chars().next().unwrap_or_default();
+        Err(Error::unexpected_char(c).at(0, idx))
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
 struct Block {
     id: usize,
     location: usize,
-    size: u8,
+    size: usize,
+}
+
+#[derive(Clone)]
+struct Disk {
+    fs: Vec<usize>,
+    space: Vec<Block>,
+    used: Vec<Block>,
+}
+
+impl Disk {
+    /// Move individual blocks into the leftmost free space
+    fn compact_blocks(&self) -> usize {
+        let mut fs = self.fs.clone();
+        let Some(mut backward_idx) = fs.len().checked_sub(1) else {
+            return 0;
+        };
+        let mut forward_idx = 0;
+        while forward_idx < backward_idx {
+            let val = fs[forward_idx];
+            if val == EMPTY {
+                if fs[backward_idx] == EMPTY {
+                    backward_idx -= 1;
+                } else {
+                    fs.swap(forward_idx, backward_idx);
+                }
+            } else {
+                forward_idx += 1;
+            }
+        }
+        score(&fs)
+    }
+    /// Move whole files into the leftmost free space that fits them
+    fn compact_files(&self) -> usize {
+        let mut fs2 = self.fs.clone();
+        let mut space = self.space.clone();
+        for block in self.used.iter().rev() {
+            let space_block = space
+                .iter_mut()
+                .find(|x| x.size >= block.size && x.location < block.location);
+            if let Some(space_block) = space_block {
+                for i in 0..block.size {
+                    fs2[i + space_block.location] = block.id;
+                    fs2[i + block.location] = EMPTY;
+                }
+                // Just leave the 0 capacity empty blocks alone, to be skipped over
+                let remaining = space_block.size - block.size;
+                space_block.size = remaining;
+                space_block.location += block.size;
+            }
+        }
+        score(&fs2)
+    }
 }
 
-pub fn solve(input: &str) -> Option<(usize, usize)> {
+fn parse(input: &str) -> Result<Disk> {
     let mut fs = Vec::new();
-    let mut space = Vec::new();
+    let mut space: Vec<Block> = Vec::new();
     let mut used = Vec::new();
-    for (id, val) in input
-        .lines()
-        .nth(0)
-        .unwrap()
-        .as_bytes()
-        .chunks(2)
-        .enumerate()
-    {
-        let file_len = get_number(val[0]);
+    let line = input.lines().next().unwrap_or_default();
+    for (id, val) in line.as_bytes().chunks(2).enumerate() {
+        let file_len = get_number(line, 2 * id)?.into();
         used.push(Block {
             id,
             location: fs.len(),
             size: file_len,
         });
-        for _ in 0..file_len {
-            fs.push(id);
-        }
-        if let Some(second) = val.get(1) {
-            let free_space = get_number(*second);
-            space.push(Block {
-                id: EMPTY,
-                location: fs.len(),
-                size: free_space,
-            });
-            for _ in 0..free_space {
-                fs.push(EMPTY);
+        fs.extend(std::iter::repeat_n(id, file_len));
+        if val.len() == 2 {
+            let free_space = get_number(line, 2 * id + 1)?.into();
+            match space.last_mut() {
+                // An empty file leaves the free space around it in one piece
+                Some(last) if last.location + last.size == fs.len() => last.size += free_space,
+                _ => space.push(Block {
+                    id: EMPTY,
+                    location: fs.len(),
+                    size: free_space,
+                }),
             }
+            fs.extend(std::iter::repeat_n(EMPTY, free_space));
         }
     }
-    let mut fs2 = fs.clone();
+    Ok(Disk { fs, space, used })
+}
 
-    // Part 1
-    let mut backward_idx = fs.len() - 1;
-    let mut forward_idx = 0;
-    while forward_idx < backward_idx {
-        let val = fs[forward_idx];
-        if val == EMPTY {
-            while fs[backward_idx] == EMPTY {
-                backward_idx -= 1;
-            }
-            let val = fs[backward_idx];
-            fs[backward_idx] = EMPTY;
-            fs[forward_idx] = val;
-        } else {
-            forward_idx += 1;
-        }
-    }
-    // Part 2
-    for block in used.into_iter().rev() {
-        let space_block = space
-            .iter_mut()
-            .find(|x| x.size >= block.size && x.location < block.location);
-        if let Some(space_block) = space_block {
-            for i in 0..block.size {
-                fs2[i as usize + space_block.location] = block.id;
-                fs2[i as usize + block.location] = EMPTY;
-            }
-            // Just leave the 0 capacity empty blocks alone, to be skipped over
-            let remaining = space_block.size - block.size;
-            space_block.size = remaining;
-            space_block.location += block.size as usize;
-        }
-    }
-    let part1 = score(&fs);
-    let part2 = score(&fs2);
-    Some((part1, part2))
+pub fn part1(input: &str) -> Result<usize> {
+    Ok(parse(input)?.compact_blocks())
+}
+
+pub fn part2(input: &str) -> Result<usize> {
+    Ok(parse(input)?.compact_files())
+}
+
+pub fn solve(input: &str) -> Result<(usize, usize)> {
+    let disk = parse(input)?;
+    Ok((disk.compact_blocks(), disk.compact_files()))
 }
 
 fn score(fs: &[usize]) -> usize {
@@ -93,11 +123,136 @@ fn score(fs: &[usize]) -> usize {
         .sum()
 }
 
+pub struct Day9;
+
+impl Solution for Day9 {
+    fn day(&self) -> u8 {
+        9
+    }
+    fn title(&self) -> &'static str {
+        "Disk Fragmenter"
+    }
+    fn part1(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part1(input))
+    }
+    fn part2(&self, input: &str) -> Result<Answer> {
+        answer(self.day(), part2(input))
+    }
+    fn solve(&self, input: &str) -> Result<(Answer, Answer)> {
+        answers(self.day(), solve(input))
+    }
+    fn bench(&self, input: &str, runs: usize) -> Result<Timings> {
+        crate::bench::stages(
+            input,
+            runs,
+            parse,
+            |disk| disk.compact_blocks(),
+            |disk| disk.compact_files(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    static INPUT: &'static str = include_str!("../input/p9.txt");
+    use super::EMPTY;
+    use proptest::prelude::*;
+
+    const EXAMPLE: &str = "2333133121414131402";
+
+    #[test]
+    fn day9_example() {
+        assert_eq!(super::part1(EXAMPLE).unwrap(), 1928);
+        assert_eq!(super::part2(EXAMPLE).unwrap(), 2858);
+        assert_eq!(super::solve(EXAMPLE).unwrap(), (1928, 2858));
+    }
+
     #[test]
     fn day9_solve() {
-        dbg!(super::solve(INPUT));
+        let Some(input) = crate::input::personal(9) else {
+            return;
+        };
+        crate::answers::assert_recorded(9, super::solve(&input).unwrap());
+    }
+
+    #[test]
+    fn day9_parse_error() {
+        let err = super::part1("12é3").unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(1), Some(3)));
+        assert!(err.to_string().contains("'é'"), "{}", err);
+    }
+
+    /// Lay out every block of the disk map, `EMPTY` for free ones
+    fn expand(map: &str) -> Vec<usize> {
+        let mut blocks = Vec::new();
+        for (idx, size) in map.bytes().map(|b| (b - b'0') as usize).enumerate() {
+            let id = if idx % 2 == 0 { idx / 2 } else { EMPTY };
+            blocks.extend(std::iter::repeat_n(id, size));
+        }
+        blocks
+    }
+
+    fn checksum(blocks: &[usize]) -> usize {
+        blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, &id)| id != EMPTY)
+            .map(|(idx, id)| idx * id)
+            .sum()
+    }
+
+    /// Move the last used block into the first free one until no free block is left of a used
+    fn naive_blocks(map: &str) -> usize {
+        let mut blocks = expand(map);
+        loop {
+            let free = blocks.iter().position(|&id| id == EMPTY);
+            let used = blocks.iter().rposition(|&id| id != EMPTY);
+            match (free, used) {
+                (Some(free), Some(used)) if free < used => blocks.swap(free, used),
+                _ => return checksum(&blocks),
+            }
+        }
+    }
+
+    /// Move each file, highest ID first, into the leftmost run of free blocks that fits it
+    fn naive_files(map: &str) -> usize {
+        let mut blocks = expand(map);
+        for id in (0..map.len().div_ceil(2)).rev() {
+            let Some(start) = blocks.iter().position(|&block| block == id) else {
+                continue;
+            };
+            let size = blocks[start..]
+                .iter()
+                .take_while(|&&block| block == id)
+                .count();
+            let fits = (0..start).find(|&free| {
+                blocks[free..free + size]
+                    .iter()
+                    .all(|&block| block == EMPTY)
+            });
+            if let Some(free) = fits {
+                blocks[free..free + size].fill(id);
+                blocks[start..start + size].fill(EMPTY);
+            }
+        }
+        checksum(&blocks)
+    }
+
+    proptest! {
+        #[test]
+        fn day9_matches_naive(sizes in prop::collection::vec((0..10u8, 0..10u8), 1..20)) {
+            let map: String = sizes
+                .iter()
+                .flat_map(|&(file, free)| [file, free])
+                .map(|size| char::from(b'0' + size))
+                .collect();
+            prop_assert_eq!(super::solve(&map).unwrap(), (naive_blocks(&map), naive_files(&map)));
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn day9_never_panics(input in crate::fuzz::mutations(EXAMPLE)) {
+            let _ = super::solve(&input);
+        }
     }
 }
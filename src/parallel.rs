@@ -0,0 +1,58 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the helpers below use the rayon thread pool, which needs the `parallel` feature
+static ENABLED: AtomicBool = AtomicBool::new(cfg!(feature = "parallel"));
+
+/// Pieces [`map_chunks`] splits its items into, enough to keep every thread busy
+const CHUNKS: usize = 64;
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Switch the thread pool on or off at runtime, e.g. to compare timings.
+///
+/// Without the `parallel` feature everything stays sequential
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled && cfg!(feature = "parallel"), Ordering::Relaxed);
+}
+
+/// Apply `f` to every item, keeping their order, so results match a sequential run
+pub fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    #[cfg(feature = "parallel")]
+    if enabled() {
+        use rayon::prelude::*;
+        return items.par_iter().map(f).collect();
+    }
+    items.iter().map(f).collect()
+}
+
+/// Apply `f` to consecutive runs of items, in order, for work that needs its own scratch
+/// space like a copy of the map
+pub fn map_chunks<T: Sync, R: Send>(items: &[T], f: impl Fn(&[T]) -> R + Sync + Send) -> Vec<R> {
+    let size = items.len().div_ceil(CHUNKS).max(1);
+    #[cfg(feature = "parallel")]
+    if enabled() {
+        use rayon::prelude::*;
+        return items.par_chunks(size).map(f).collect();
+    }
+    items.chunks(size).map(f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{map, map_chunks};
+
+    #[test]
+    fn keeps_order() {
+        let items: Vec<u64> = (0..1000).collect();
+        assert_eq!(
+            map(&items, |x| x * 2),
+            (0..2000).step_by(2).collect::<Vec<_>>()
+        );
+        let sums = map_chunks(&items, |chunk| chunk.iter().sum::<u64>());
+        assert_eq!(sums.len(), 63);
+        assert_eq!(sums.iter().sum::<u64>(), 499500);
+        assert!(map_chunks(&[] as &[u64], |chunk| chunk.len()).is_empty());
+    }
+}
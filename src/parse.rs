@@ -0,0 +1,172 @@
+use std::str::FromStr;
+
+use crate::error::parse_token;
+use crate::{Error, Grid, Result};
+
+/// Every integer in a line, skipping whatever is between them.
+///
+/// A `-` right before a digit is read as a sign, so `v=3,-3` gives `3, -3` and `X+-3` gives `-3`;
+/// use [`uints`] where a `-` is only ever a separator.
+///
+/// Lines come as a 0-based index and text, like `lines().enumerate()` produces
+pub fn ints<T: FromStr>(line_idx: usize, line: &str) -> Result<Vec<T>> {
+    numbers(line_idx, line, true)
+}
+
+/// Every run of digits in a line, skipping whatever is between them, `-` included
+pub fn uints<T: FromStr>(line_idx: usize, line: &str) -> Result<Vec<T>> {
+    numbers(line_idx, line, false)
+}
+
+fn numbers<T: FromStr>(line_idx: usize, line: &str, signed: bool) -> Result<Vec<T>> {
+    let bytes = line.as_bytes();
+    let mut out = Vec::new();
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let negative =
+            signed && bytes[idx] == b'-' && bytes.get(idx + 1).is_some_and(u8::is_ascii_digit);
+        if !negative && !bytes[idx].is_ascii_digit() {
+            idx += 1;
+            continue;
+        }
+        let start = idx;
+        idx += 1;
+        while idx < bytes.len() && bytes[idx].is_ascii_digit() {
+            idx += 1;
+        }
+        out.push(parse_token(line_idx, line, &line[start..idx])?);
+    }
+    Ok(out)
+}
+
+/// Exactly `N` integers in a line, e.g. the four in `p=0,4 v=3,-3`
+pub fn fixed_ints<T: FromStr, const N: usize>(line_idx: usize, line: &str) -> Result<[T; N]> {
+    exactly(line_idx, ints(line_idx, line)?)
+}
+
+/// Exactly `N` runs of digits in a line, e.g. the two in `Button A: X+94, Y+34`
+pub fn fixed_uints<T: FromStr, const N: usize>(line_idx: usize, line: &str) -> Result<[T; N]> {
+    exactly(line_idx, uints(line_idx, line)?)
+}
+
+fn exactly<T, const N: usize>(line_idx: usize, found: Vec<T>) -> Result<[T; N]> {
+    let count = found.len();
+    found.try_into().map_err(|_| {
+        let message = format!("expected {} numbers, found {}", N, count);
+        Error::parse(message).at_line(line_idx)
+    })
+}
+
+/// Parse the comma separated values in `list`, a slice of `line`
+pub fn comma_list<T: FromStr>(line_idx: usize, line: &str, list: &str) -> Result<Vec<T>> {
+    list.split(',')
+        .map(|token| parse_token(line_idx, line, token.trim()))
+        .collect()
+}
+
+/// Parse the whitespace separated values in `list`, a slice of `line`
+pub fn words<T: FromStr>(line_idx: usize, line: &str, list: &str) -> Result<Vec<T>> {
+    list.split_whitespace()
+        .map(|token| parse_token(line_idx, line, token))
+        .collect()
+}
+
+/// Split the input into blocks of numbered lines, separated by blank lines
+pub fn sections(input: &str) -> Vec<Vec<(usize, &str)>> {
+    let mut out = vec![Vec::new()];
+    for (line_idx, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            if out.last().is_some_and(|section| !section.is_empty()) {
+                out.push(Vec::new());
+            }
+        } else {
+            out.last_mut().unwrap().push((line_idx, line));
+        }
+    }
+    out.retain(|section| !section.is_empty());
+    out
+}
+
+/// Where each marker of a map is, as `(row, col)`, in the order the markers were given
+pub type Markers<const N: usize> = [(usize, usize); N];
+
+/// Parse a character map like [`Grid::parse_lines`], also finding where each of `markers` is.
+///
+/// Every marker must appear exactly once. `cell` converts every character, markers included
+pub fn grid_with_markers<'a, T, const N: usize>(
+    lines: impl IntoIterator<Item = (usize, &'a str)>,
+    markers: [char; N],
+    mut cell: impl FnMut(char) -> Result<T>,
+) -> Result<(Grid<T>, Markers<N>)> {
+    let mut found = [None; N];
+    let grid = Grid::parse_lines(lines, |position, c| {
+        if let Some(idx) = markers.iter().position(|&marker| marker == c) {
+            if found[idx].replace(position).is_some() {
+                return Err(Error::parse(format!("found a second {:?}", c)));
+            }
+        }
+        cell(c)
+    })?;
+    let mut positions = [(0, 0); N];
+    for ((position, found), marker) in positions.iter_mut().zip(found).zip(markers) {
+        *position = found.ok_or_else(|| Error::parse(format!("the map has no {:?}", marker)))?;
+    }
+    Ok((grid, positions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        comma_list, fixed_ints, fixed_uints, grid_with_markers, ints, sections, uints, words,
+    };
+    use crate::Error;
+
+    #[test]
+    fn integers() {
+        let found: Vec<i64> = ints(0, "p=0,4 v=3,-3 x-y 12").unwrap();
+        assert_eq!(found, [0, 4, 3, -3, 12]);
+        let [x, y] = fixed_uints::<u32, 2>(0, "Button A: X+94, Y+34").unwrap();
+        assert_eq!((x, y), (94, 34));
+        let err = fixed_ints::<u32, 2>(4, "Prize: X=8400").unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(5), None));
+        // Negative numbers are still found, so unsigned parsing points at them
+        let err = ints::<u32>(0, "1, -2").unwrap_err();
+        assert_eq!(err.column(), Some(4));
+        let found: Vec<u32> = uints(0, "1, -2 x-3").unwrap();
+        assert_eq!(found, [1, 2, 3]);
+    }
+
+    #[test]
+    fn lists() {
+        let line = "Program: 0,1, 5,4";
+        let program: Vec<u8> = comma_list(0, line, &line[9..]).unwrap();
+        assert_eq!(program, [0, 1, 5, 4]);
+        let line = "190: 10 x";
+        let err = words::<u64>(2, line, &line[4..]).unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(3), Some(9)));
+    }
+
+    #[test]
+    fn blank_line_sections() {
+        let found = sections("a\nb\n\n\nc\n\n");
+        assert_eq!(found, [vec![(0, "a"), (1, "b")], vec![(4, "c")]]);
+        assert!(sections("\n").is_empty());
+    }
+
+    #[test]
+    fn markers() {
+        let wall = |c| match c {
+            '#' => Ok(true),
+            '.' | 'S' | 'E' => Ok(false),
+            _ => Err(Error::unexpected_char(c)),
+        };
+        let map = "#S.\n.E#\n";
+        let (grid, [start, end]) =
+            grid_with_markers(map.lines().enumerate(), ['S', 'E'], wall).unwrap();
+        assert_eq!((start, end), ((0, 1), (1, 1)));
+        assert!(grid[(1, 2)]);
+        let err = grid_with_markers("SS.\n".lines().enumerate(), ['S'], wall).unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(1), Some(2)));
+        assert!(grid_with_markers("#S.\n".lines().enumerate(), ['S', 'E'], wall).is_err());
+    }
+}
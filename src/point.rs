@@ -0,0 +1,125 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use crate::Direction;
+
+/// Signed 2D point, or the offset between two points.
+///
+/// `x` counts columns and `y` counts rows, so `y` grows towards the bottom of a map
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+/// Points double as displacements between points
+pub type Vec2 = Point;
+
+impl Point {
+    pub const ORIGIN: Point = Point::new(0, 0);
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+    /// The point at a `(row, col)` grid position
+    pub fn from_grid((row, col): (usize, usize)) -> Self {
+        Self::new(col as i64, row as i64)
+    }
+    /// The `(row, col)` grid position of the point, if it lies inside `(num_rows, num_cols)`
+    pub fn to_grid(self, (num_rows, num_cols): (usize, usize)) -> Option<(usize, usize)> {
+        let row = usize::try_from(self.y).ok().filter(|&row| row < num_rows)?;
+        let col = usize::try_from(self.x).ok().filter(|&col| col < num_cols)?;
+        Some((row, col))
+    }
+    pub fn manhattan(self, other: Point) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+    /// Wrap around the edges of a `width` by `height` torus, back into `0..width, 0..height`
+    pub fn wrap(self, width: i64, height: i64) -> Self {
+        Self::new(self.x.rem_euclid(width), self.y.rem_euclid(height))
+    }
+}
+
+impl From<Direction> for Point {
+    fn from(direction: Direction) -> Self {
+        let (row, col) = direction.offset();
+        Self::new(col as i64, row as i64)
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Point;
+    use crate::Direction;
+
+    #[test]
+    fn arithmetic() {
+        let a = Point::new(3, -2);
+        let b = Point::new(-1, 5);
+        assert_eq!(a + b, Point::new(2, 3));
+        assert_eq!(a - b, Point::new(4, -7));
+        assert_eq!(a * 3, Point::new(9, -6));
+        assert_eq!(-a, Point::new(-3, 2));
+        assert_eq!(a.manhattan(b), 11);
+        assert_eq!(Point::from(Direction::NorthWest), Point::new(-1, -1));
+    }
+
+    #[test]
+    fn wrap_around() {
+        assert_eq!(Point::new(-1, 7).wrap(11, 7), Point::new(10, 0));
+        let robot = Point::new(2, 4) + Point::new(2, -3) * 5;
+        assert_eq!(robot.wrap(11, 7), Point::new(1, 3));
+    }
+
+    #[test]
+    fn grid_positions() {
+        let bounds = (2, 3);
+        assert_eq!(Point::new(2, 1).to_grid(bounds), Some((1, 2)));
+        assert_eq!(Point::new(3, 1).to_grid(bounds), None);
+        assert_eq!(Point::new(0, -1).to_grid(bounds), None);
+        assert_eq!(Point::from_grid((1, 2)), Point::new(2, 1));
+    }
+}
@@ -0,0 +1,251 @@
+use std::any::Any;
+use std::fmt::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+use crate::{Error, ErrorKind, Result, Solution, Verdict};
+
+/// Outcome of solving one day, in a shape scripts can compare between runs
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub day: u8,
+    pub title: &'static str,
+    /// `None` when the day's input is missing
+    pub run: Option<Run>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Run {
+    /// [`input_hash`] of the input, to tell apart runs on different inputs
+    pub input_hash: u64,
+    pub answers: Result<(String, String)>,
+    pub time: Duration,
+    /// Verdicts on both answers, when they are checked against an answers file
+    pub verdicts: Option<[Verdict; 2]>,
+}
+
+impl Report {
+    /// Solve a day, or report its input as missing
+    pub fn solve(solution: &dyn Solution, input: Option<&str>) -> Self {
+        Self {
+            day: solution.day(),
+            title: solution.title(),
+            run: input.map(|input| Run::solve(solution, input)),
+        }
+    }
+}
+
+impl Run {
+    /// Solve a day and time it, leaving the answers unchecked. A panic fails only this day
+    pub fn solve(solution: &dyn Solution, input: &str) -> Self {
+        let start = Instant::now();
+        let answers = panic::catch_unwind(AssertUnwindSafe(|| solution.solve(input)))
+            .unwrap_or_else(|payload| {
+                Err(Error::panic(panic_message(&*payload)).with_day(solution.day()))
+            });
+        let time = start.elapsed();
+        Self {
+            input_hash: input_hash(input),
+            answers: answers.map(|(part1, part2)| (part1.to_string(), part2.to_string())),
+            time,
+            verdicts: None,
+        }
+    }
+}
+
+/// Text of a caught panic, when it carries one
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "panicked".to_string()
+    }
+}
+
+/// FNV-1a hash of an input, which unlike the hashers in `std` is the same for every build
+pub fn input_hash(input: &str) -> u64 {
+    input.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// `text` as a JSON string literal
+fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_option(value: Option<impl ToString>) -> String {
+    value.map_or("null".to_string(), |value| value.to_string())
+}
+
+/// An array of days with their answers, time in nanoseconds, input hash and error, all `null`
+/// where they do not apply
+pub fn to_json(reports: &[Report]) -> String {
+    let days: Vec<_> = reports
+        .iter()
+        .map(|report| {
+            let mut out = format!(
+                "  {{\"day\": {}, \"title\": {}, ",
+                report.day,
+                json_string(report.title)
+            );
+            let Some(run) = &report.run else {
+                out.push_str("\"input_hash\": null, \"part1\": null, \"part2\": null, ");
+                out.push_str("\"time_ns\": null, ");
+                out.push_str("\"error\": {\"kind\": \"missing_input\", \"message\": \"no input\"}}");
+                return out;
+            };
+            write!(out, "\"input_hash\": \"{:016x}\", ", run.input_hash).unwrap();
+            match &run.answers {
+                Ok((part1, part2)) => write!(
+                    out,
+                    "\"part1\": {}, \"part2\": {}, ",
+                    json_string(part1),
+                    json_string(part2)
+                ),
+                Err(_) => write!(out, "\"part1\": null, \"part2\": null, "),
+            }
+            .unwrap();
+            write!(out, "\"time_ns\": {}, ", run.time.as_nanos()).unwrap();
+            if let Some(verdicts) = &run.verdicts {
+                let [part1, part2] = verdicts.each_ref().map(|v| json_string(&v.to_string()));
+                write!(out, "\"checks\": [{}, {}], ", part1, part2).unwrap();
+            }
+            match &run.answers {
+                Ok(_) => out.push_str("\"error\": null}"),
+                Err(err) => {
+                    let (kind, message) = match err.kind() {
+                        ErrorKind::Parse(message) => ("parse", message),
+                        ErrorKind::NoSolution(message) => ("no_solution", message),
+                        ErrorKind::Panic(message) => ("panic", message),
+                    };
+                    write!(
+                        out,
+                        "\"error\": {{\"kind\": \"{}\", \"message\": {}, \"line\": {}, \"column\": {}}}}}",
+                        kind,
+                        json_string(message),
+                        json_option(err.line()),
+                        json_option(err.column())
+                    )
+                    .unwrap();
+                }
+            }
+            out
+        })
+        .collect();
+    format!("[\n{}\n]\n", days.join(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{input_hash, json_string, to_json, Report, Run};
+    use crate::{Answer, Error, ErrorKind, Result, Solution, Timings, Verdict};
+    use std::time::Duration;
+
+    /// A day whose solver has a bug
+    struct Broken;
+
+    impl Solution for Broken {
+        fn day(&self) -> u8 {
+            7
+        }
+        fn title(&self) -> &'static str {
+            "Broken"
+        }
+        fn part1(&self, _: &str) -> Result<Answer> {
+            panic!("index out of bounds")
+        }
+        fn part2(&self, _: &str) -> Result<Answer> {
+            Ok(Box::new(2))
+        }
+        fn bench(&self, _: &str, _: usize) -> Result<Timings> {
+            Err(Error::no_solution("not timed"))
+        }
+    }
+
+    #[test]
+    fn stable_hash() {
+        assert_eq!(input_hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(input_hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(input_hash("1 2\n"), input_hash("1 2"));
+    }
+
+    #[test]
+    fn panic_fails_the_day() {
+        let report = Report::solve(&Broken, Some("input"));
+        let err = report.run.unwrap().answers.unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &ErrorKind::Panic("index out of bounds".to_string())
+        );
+        assert_eq!(err.day(), Some(7));
+        let json = to_json(&[Report::solve(&Broken, Some("input"))]);
+        assert!(json.contains("\"error\": {\"kind\": \"panic\""), "{}", json);
+    }
+
+    #[test]
+    fn json() {
+        assert_eq!(json_string("a \"b\"\\\n\u{1}"), r#""a \"b\"\\\n\u0001""#);
+        let solved = Run {
+            input_hash: 0xff,
+            answers: Ok(("11".to_string(), "co,de".to_string())),
+            time: Duration::from_micros(5),
+            verdicts: Some([Verdict::Pass, Verdict::Missing]),
+        };
+        let failed = Run {
+            input_hash: 1,
+            answers: Err(Error::parse("expected a number").at(2, 4).with_day(3)),
+            time: Duration::from_nanos(7),
+            verdicts: None,
+        };
+        let reports = [
+            Report {
+                day: 1,
+                title: "One",
+                run: Some(solved),
+            },
+            Report {
+                day: 2,
+                title: "Two",
+                run: None,
+            },
+            Report {
+                day: 3,
+                title: "Three",
+                run: Some(failed),
+            },
+        ];
+        let json = to_json(&reports);
+        let lines: Vec<_> = json.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(
+            lines[1],
+            "  {\"day\": 1, \"title\": \"One\", \"input_hash\": \"00000000000000ff\", \
+            \"part1\": \"11\", \"part2\": \"co,de\", \"time_ns\": 5000, \
+            \"checks\": [\"pass\", \"missing\"], \"error\": null},"
+        );
+        assert!(lines[2].contains("\"input_hash\": null"));
+        assert!(lines[2].contains("\"kind\": \"missing_input\""));
+        assert_eq!(
+            lines[3],
+            "  {\"day\": 3, \"title\": \"Three\", \"input_hash\": \"0000000000000001\", \
+            \"part1\": null, \"part2\": null, \"time_ns\": 7, \"error\": {\"kind\": \"parse\", \
+            \"message\": \"expected a number\", \"line\": 3, \"column\": 5}}"
+        );
+    }
+}
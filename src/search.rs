@@ -0,0 +1,288 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// Outcome of a shortest path search, with every shortest path back to the start.
+///
+/// Searches stop once they reach a goal, so distances are only final up to the goal's
+#[derive(Debug, Clone)]
+pub struct Search<N> {
+    start: N,
+    goal: Option<N>,
+    dist: HashMap<N, u64>,
+    prev: HashMap<N, Vec<N>>,
+}
+
+impl<N: Copy + Eq + Hash> Search<N> {
+    fn new(start: N) -> Self {
+        Self {
+            start,
+            goal: None,
+            dist: HashMap::from([(start, 0)]),
+            prev: HashMap::new(),
+        }
+    }
+    /// Record that `node` can be reached through `from` at `cost`, returning whether it is new
+    /// or cheaper than before and so needs to be expanded
+    fn relax(&mut self, from: N, node: N, cost: u64) -> bool {
+        match self.dist.get(&node) {
+            Some(&best) if cost > best => false,
+            Some(&best) if cost == best => {
+                self.prev.entry(node).or_default().push(from);
+                false
+            }
+            _ => {
+                self.dist.insert(node, cost);
+                self.prev.insert(node, vec![from]);
+                true
+            }
+        }
+    }
+    pub fn start(&self) -> N {
+        self.start
+    }
+    /// The goal the search stopped at, if it reached one
+    pub fn goal(&self) -> Option<N> {
+        self.goal
+    }
+    pub fn distance(&self, node: N) -> Option<u64> {
+        self.dist.get(&node).copied()
+    }
+    pub fn distances(&self) -> &HashMap<N, u64> {
+        &self.dist
+    }
+    /// Every node that comes right before `node` on some shortest path
+    pub fn predecessors(&self, node: N) -> &[N] {
+        self.prev.get(&node).map_or(&[], Vec::as_slice)
+    }
+    /// One shortest path from the start to `node`, including both ends.
+    ///
+    /// Zero cost edges make nodes at the same distance predecessors of each other, so this
+    /// backtracks out of any predecessor it has already been through
+    pub fn path(&self, node: N) -> Option<Vec<N>> {
+        self.distance(node)?;
+        let mut seen = HashSet::from([node]);
+        // Each node of the path so far, with how many of its predecessors have been tried
+        let mut path = vec![(node, 0)];
+        while let Some((current, tried)) = path.last_mut() {
+            if *current == self.start {
+                return Some(path.into_iter().rev().map(|(node, _)| node).collect());
+            }
+            match self.predecessors(*current).get(*tried) {
+                Some(&prev) => {
+                    *tried += 1;
+                    if seen.insert(prev) {
+                        path.push((prev, 0));
+                    }
+                }
+                None => {
+                    path.pop();
+                }
+            }
+        }
+        None
+    }
+    /// Every node on any shortest path from the start to `node`, including both ends
+    pub fn on_shortest_paths(&self, node: N) -> HashSet<N> {
+        let mut visited = HashSet::new();
+        if self.distance(node).is_none() {
+            return visited;
+        }
+        let mut stack = vec![node];
+        while let Some(current) = stack.pop() {
+            if visited.insert(current) {
+                stack.extend(self.predecessors(current));
+            }
+        }
+        visited
+    }
+}
+
+/// Breadth first search over unit cost edges
+pub fn bfs<N, I>(
+    start: N,
+    mut neighbors: impl FnMut(N) -> I,
+    mut is_goal: impl FnMut(N) -> bool,
+) -> Search<N>
+where
+    N: Copy + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut search = Search::new(start);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((node, cost)) = queue.pop_front() {
+        if is_goal(node) {
+            search.goal = Some(node);
+            break;
+        }
+        for next in neighbors(node) {
+            if search.relax(node, next, cost + 1) {
+                queue.push_back((next, cost + 1));
+            }
+        }
+    }
+    search
+}
+
+/// Dijkstra's algorithm over weighted edges, given as `(node, cost)` pairs
+pub fn dijkstra<N, I>(
+    start: N,
+    neighbors: impl FnMut(N) -> I,
+    is_goal: impl FnMut(N) -> bool,
+) -> Search<N>
+where
+    N: Copy + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    astar(start, neighbors, |_| 0, is_goal)
+}
+
+/// A* search, which needs a heuristic that never overestimates the remaining cost to a goal
+pub fn astar<N, I>(
+    start: N,
+    mut neighbors: impl FnMut(N) -> I,
+    mut heuristic: impl FnMut(N) -> u64,
+    mut is_goal: impl FnMut(N) -> bool,
+) -> Search<N>
+where
+    N: Copy + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let mut search = Search::new(start);
+    let mut heap = BinaryHeap::new();
+    heap.push(State {
+        priority: heuristic(start),
+        cost: 0,
+        node: start,
+    });
+    while let Some(State { cost, node, .. }) = heap.pop() {
+        // Stale entry, the node was reached more cheaply since
+        if search.distance(node).is_some_and(|best| cost > best) {
+            continue;
+        }
+        if is_goal(node) {
+            search.goal = Some(node);
+            break;
+        }
+        for (next, step) in neighbors(node) {
+            let cost = cost + step;
+            if search.relax(node, next, cost) {
+                heap.push(State {
+                    priority: cost + heuristic(next),
+                    cost,
+                    node: next,
+                });
+            }
+        }
+    }
+    search
+}
+
+/// Entry in the priority queue, ordered so the heap pops the lowest priority first
+struct State<N> {
+    priority: u64,
+    cost: u64,
+    node: N,
+}
+
+impl<N> Ord for State<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+impl<N> PartialOrd for State<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N> PartialEq for State<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N> Eq for State<N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{astar, bfs, dijkstra, Search};
+    use std::collections::HashMap;
+
+    /// A 4 x 4 open grid with a wall at (1, 1) and (1, 2)
+    fn open(position: (usize, usize)) -> Vec<(usize, usize)> {
+        let (row, col) = position;
+        let mut out = Vec::new();
+        for next in [
+            (row.wrapping_sub(1), col),
+            (row, col + 1),
+            (row + 1, col),
+            (row, col.wrapping_sub(1)),
+        ] {
+            if next.0 < 4 && next.1 < 4 && next != (1, 1) && next != (1, 2) {
+                out.push(next);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn bfs_paths() {
+        let search = bfs((0, 0), open, |node| node == (3, 3));
+        assert_eq!(search.goal(), Some((3, 3)));
+        assert_eq!(search.distance((3, 3)), Some(6));
+        let path = search.path((3, 3)).unwrap();
+        assert_eq!((path.len(), path[0], path[6]), (7, (0, 0), (3, 3)));
+        // Every cell except the walls lies on some shortest path to the far corner
+        assert_eq!(search.on_shortest_paths((3, 3)).len(), 14);
+        assert_eq!(search.predecessors((0, 0)), []);
+    }
+
+    #[test]
+    fn weighted_paths() {
+        // Entering row 1 costs 10, so only the left edge, which crosses it once, is cheapest
+        let weighted = |position: (usize, usize)| {
+            open(position)
+                .into_iter()
+                .map(|next| (next, if next.0 == 1 { 10 } else { 1 }))
+        };
+        let search = dijkstra((0, 0), weighted, |node| node == (2, 2));
+        assert_eq!(search.distance((2, 2)), Some(13));
+        assert_eq!(search.predecessors((2, 2)), [(2, 1)]);
+        let manhattan = |(row, col): (usize, usize)| (row.abs_diff(2) + col.abs_diff(2)) as u64;
+        let guided = astar((0, 0), weighted, manhattan, |node| node == (2, 2));
+        assert_eq!(guided.distance((2, 2)), Some(13));
+        assert_eq!(guided.path((2, 2)), search.path((2, 2)));
+    }
+
+    #[test]
+    fn zero_cost_cycle() {
+        // 1 and 2 lead to each other for free, so each is a predecessor of the other
+        let edges = |node: u8| match node {
+            0 => vec![(1, 1)],
+            1 => vec![(2, 0)],
+            2 => vec![(1, 0), (3, 1)],
+            _ => vec![],
+        };
+        let search = dijkstra(0, edges, |node| node == 3);
+        assert_eq!(search.path(3), Some(vec![0, 1, 2, 3]));
+        // The same ties, listing the node at the same distance first
+        let search = Search {
+            start: 0,
+            goal: None,
+            dist: HashMap::from([(0, 0), (1, 1), (2, 1)]),
+            prev: HashMap::from([(1, vec![2, 0]), (2, vec![1])]),
+        };
+        assert_eq!(search.path(2), Some(vec![0, 1, 2]));
+        assert_eq!(search.path(1), Some(vec![0, 1]));
+    }
+
+    #[test]
+    fn unreachable() {
+        let search = bfs((0, 0), |_| [], |node| node == (3, 3));
+        assert_eq!(search.goal(), None);
+        assert_eq!(search.path((3, 3)), None);
+        assert!(search.on_shortest_paths((3, 3)).is_empty());
+    }
+}
@@ -0,0 +1,280 @@
+use std::io;
+use std::time::Duration;
+
+/// Environment variable holding the session cookie of a logged in user
+pub const SESSION_VAR: &str = "ADVENT_SESSION";
+/// Environment variable that overrides the site, e.g. to point at a local stand-in
+pub const BASE_URL_VAR: &str = "ADVENT_BASE_URL";
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+pub const YEAR: u16 = 2024;
+
+const USER_AGENT: &str = concat!("advent2024/", env!("CARGO_PKG_VERSION"));
+
+/// Talks to the Advent of Code site, or anything serving the same paths, as a logged in user
+#[derive(Debug, Clone)]
+pub struct Client {
+    base_url: String,
+    session: String,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(base_url: impl Into<String>, session: impl Into<String>) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Self {
+            base_url,
+            session: session.into(),
+            agent: ureq::AgentBuilder::new().user_agent(USER_AGENT).build(),
+        }
+    }
+    /// Use the session in `ADVENT_SESSION` and the site in `ADVENT_BASE_URL`, if a session is set
+    pub fn from_env() -> Option<Self> {
+        let session = std::env::var(SESSION_VAR)
+            .ok()
+            .filter(|s| !s.trim().is_empty())?;
+        let base_url = match std::env::var(BASE_URL_VAR) {
+            Ok(url) if !url.is_empty() => url,
+            _ => DEFAULT_BASE_URL.to_string(),
+        };
+        Some(Self::new(base_url, session.trim()))
+    }
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+    /// URL of a day's puzzle page, or of a page under it like `input`
+    fn url(&self, day: u8, page: &str) -> String {
+        format!("{}/{}/day/{}/{}", self.base_url, YEAR, day, page)
+    }
+    fn cookie(&self) -> String {
+        format!("session={}", self.session)
+    }
+    /// Send an answer to one part of a day, returning what the site made of it
+    pub fn submit(&self, day: u8, part: u8, answer: &str) -> io::Result<Reply> {
+        let page = self
+            .agent
+            .post(&self.url(day, "answer"))
+            .set("Cookie", &self.cookie())
+            .send_form(&[("level", &part.to_string()), ("answer", answer)])
+            .map_err(http_error)?
+            .into_string()?;
+        Ok(Reply::parse(&page))
+    }
+    /// Download a day's personal input
+    pub fn input(&self, day: u8) -> io::Result<String> {
+        self.agent
+            .get(&self.url(day, "input"))
+            .set("Cookie", &self.cookie())
+            .call()
+            .map_err(http_error)?
+            .into_string()
+    }
+}
+
+/// What the site replied to a submitted answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    Correct,
+    /// Wrong, maybe with a hint like "too high", and how long until the next answer is accepted
+    Wrong {
+        hint: Option<String>,
+        wait: Option<Duration>,
+    },
+    /// Not checked, because the previous answer was too recent
+    TooSoon(Duration),
+    /// Not checked, because the part is already solved
+    AlreadySolved,
+    /// Anything else, as the text of the reply
+    Unknown(String),
+}
+
+impl Reply {
+    /// Make sense of the page the site sends back, which explains the outcome in prose
+    pub fn parse(page: &str) -> Self {
+        let text = article_text(page);
+        if text.contains("That's the right answer") {
+            Reply::Correct
+        } else if text.contains("That's not the right answer") {
+            let hint = ["too high", "too low"]
+                .into_iter()
+                .find(|hint| text.contains(&format!("your answer is {}", hint)));
+            Reply::Wrong {
+                hint: hint.map(String::from),
+                wait: wait_time(&text),
+            }
+        } else if text.contains("You gave an answer too recently") {
+            match wait_time(&text) {
+                Some(wait) => Reply::TooSoon(wait),
+                None => Reply::Unknown(text),
+            }
+        } else if text.contains("Did you already complete it") {
+            Reply::AlreadySolved
+        } else {
+            Reply::Unknown(text)
+        }
+    }
+}
+
+/// Text of the page's `<article>`, or of the whole page, without tags
+fn article_text(page: &str) -> String {
+    let article = match (page.find("<article"), page.find("</article>")) {
+        (Some(start), Some(end)) if start < end => &page[start..end],
+        _ => page,
+    };
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in article.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Cooldown from "you have 1m 5s left to wait" or "Please wait one minute before trying again"
+fn wait_time(text: &str) -> Option<Duration> {
+    if let Some(end) = text.find(" left to wait") {
+        let start = text[..end].rfind("have ")? + "have ".len();
+        let mut seconds = 0;
+        for amount in text[start..end].split_whitespace() {
+            let (count, unit) = amount.split_at(amount.len().checked_sub(1)?);
+            let count: u64 = count.parse().ok()?;
+            seconds += count
+                * match unit {
+                    "s" => 1,
+                    "m" => 60,
+                    "h" => 3600,
+                    _ => return None,
+                };
+        }
+        return Some(Duration::from_secs(seconds));
+    }
+    let start = text.find("Please wait ")? + "Please wait ".len();
+    let mut words = text[start..].split_whitespace();
+    let count = match words.next()? {
+        "one" | "a" => 1,
+        count => count.parse().ok()?,
+    };
+    let unit = match words.next()? {
+        unit if unit.starts_with("second") => 1,
+        unit if unit.starts_with("minute") => 60,
+        unit if unit.starts_with("hour") => 3600,
+        _ => return None,
+    };
+    Some(Duration::from_secs(count * unit))
+}
+
+fn http_error(err: ureq::Error) -> io::Error {
+    match err {
+        ureq::Error::Status(code, response) => io::Error::other(format!(
+            "{} responded {} {}",
+            response.get_url(),
+            code,
+            response.status_text()
+        )),
+        ureq::Error::Transport(transport) => io::Error::other(transport),
+    }
+}
+
+/// A local stand-in for the site, answering each connection with the next canned response
+#[cfg(test)]
+pub(crate) mod stand_in {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread::{self, JoinHandle};
+
+    /// Method, path, cookie and body of a request the stand-in received
+    #[derive(Debug, Clone, Default)]
+    pub(crate) struct Request {
+        pub(crate) method: String,
+        pub(crate) path: String,
+        pub(crate) cookie: String,
+        pub(crate) body: String,
+    }
+
+    /// Serve `(status, body)` responses in order, returning the base URL and every request once
+    /// all responses have been sent
+    pub(crate) fn serve(responses: Vec<(u16, &'static str)>) -> (String, JoinHandle<Vec<Request>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let mut request = Request::default();
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut words = line.split_whitespace();
+                request.method = words.next().unwrap_or_default().to_string();
+                request.path = words.next().unwrap_or_default().to_string();
+                let mut length = 0;
+                loop {
+                    line.clear();
+                    reader.read_line(&mut line).unwrap();
+                    let Some((name, value)) = line.trim_end().split_once(": ") else {
+                        break;
+                    };
+                    match name.to_ascii_lowercase().as_str() {
+                        "cookie" => request.cookie = value.to_string(),
+                        "content-length" => length = value.parse().unwrap(),
+                        _ => {}
+                    }
+                }
+                let mut body_bytes = vec![0; length];
+                reader.read_exact(&mut body_bytes).unwrap();
+                request.body = String::from_utf8(body_bytes).unwrap();
+                requests.push(request);
+                let response = format!(
+                    "HTTP/1.1 {} Stand-in\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                reader.get_mut().write_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+        (base_url, handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{stand_in, Client, Reply};
+    use std::time::Duration;
+
+    #[test]
+    fn download_input() {
+        let (base_url, server) = stand_in::serve(vec![(200, "1 2\n3 4\n"), (404, "Not Found")]);
+        let client = Client::new(format!("{}/", base_url), "secret");
+        assert_eq!(client.input(1).unwrap(), "1 2\n3 4\n");
+        let err = client.input(26).unwrap_err();
+        assert!(err.to_string().contains("404"), "{}", err);
+        let requests = server.join().unwrap();
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].path, "/2024/day/1/input");
+        assert_eq!(requests[0].cookie, "session=secret");
+    }
+
+    #[test]
+    fn replies() {
+        let page = "<html><main><article><p>That's not the right answer; your answer is too \
+            low. <a href=\"/2024/day/1\">Please wait one minute before trying again.</a></p>\
+            </article></main></html>";
+        let wait = Some(Duration::from_secs(60));
+        let hint = Some("too low".to_string());
+        assert_eq!(Reply::parse(page), Reply::Wrong { hint, wait });
+        let page = "<article><p>You gave an answer too recently; you have 1m 5s left to wait.</p>";
+        assert_eq!(Reply::parse(page), Reply::TooSoon(Duration::from_secs(65)));
+        let page =
+            "<article><p>That's the right answer! You are one gold star closer.</p></article>";
+        assert_eq!(Reply::parse(page), Reply::Correct);
+        let page = "<article><p>You don't seem to be solving the right level.  Did you already \
+            complete it?</p></article>";
+        assert_eq!(Reply::parse(page), Reply::AlreadySolved);
+        assert_eq!(Reply::parse("<p>Hi</p>"), Reply::Unknown("Hi".to_string()));
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::site::{Client, Reply};
+use crate::{Answers, Error, InputLoader, Result};
+
+/// Name of the file of past submissions, kept next to the personal inputs
+pub const SUBMISSIONS_FILE: &str = "submissions.txt";
+
+/// What has been learned from earlier submissions: wrong answers, and when the site
+/// accepts the next one.
+///
+/// Stored as plain text, one `<day> <part> <wrong answer>` per line, and a
+/// `wait <unix seconds>` line while a cooldown is running
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Submissions {
+    wrong: BTreeMap<(u8, u8), BTreeSet<String>>,
+    wait_until: Option<SystemTime>,
+}
+
+impl Submissions {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut submissions = Self::default();
+        for (line_idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || Error::parse("expected <day> <part> <answer> or wait <seconds>");
+            let mut fields = line.splitn(3, char::is_whitespace);
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some("wait"), Some(seconds), None) => {
+                    let seconds = seconds.parse().map_err(|_| invalid().at_line(line_idx))?;
+                    submissions.wait_until = Some(UNIX_EPOCH + Duration::from_secs(seconds));
+                }
+                (Some(day), Some(part @ ("1" | "2")), Some(answer)) => {
+                    let day = day.parse().map_err(|_| invalid().at_line(line_idx))?;
+                    let part = if part == "1" { 1 } else { 2 };
+                    submissions.record_wrong(day, part, answer.trim());
+                }
+                _ => return Err(invalid().at_line(line_idx)),
+            }
+        }
+        Ok(submissions)
+    }
+    /// Where the submissions file lives, `submissions.txt` in the input directory
+    pub fn path(loader: &InputLoader) -> PathBuf {
+        loader.dir().join(SUBMISSIONS_FILE)
+    }
+    pub fn is_wrong(&self, day: u8, part: u8, answer: &str) -> bool {
+        self.wrong
+            .get(&(day, part))
+            .is_some_and(|answers| answers.contains(answer))
+    }
+    pub fn record_wrong(&mut self, day: u8, part: u8, answer: &str) {
+        self.wrong
+            .entry((day, part))
+            .or_default()
+            .insert(answer.to_string());
+    }
+    /// How long until the site accepts another answer, if it is still waiting
+    pub fn cooldown(&self, now: SystemTime) -> Option<Duration> {
+        let wait = self.wait_until?.duration_since(now).ok()?;
+        (!wait.is_zero()).then_some(wait)
+    }
+    pub fn wait(&mut self, now: SystemTime, wait: Duration) {
+        self.wait_until = Some(now + wait);
+    }
+}
+
+impl Display for Submissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# <day> <part> <wrong answer>, or wait <unix seconds>")?;
+        if let Some(until) = self.wait_until {
+            let since_epoch = until.duration_since(UNIX_EPOCH).unwrap_or_default();
+            // Round up, so the cooldown never ends early
+            let seconds = since_epoch.as_secs() + u64::from(since_epoch.subsec_nanos() > 0);
+            writeln!(f, "wait {}", seconds)?;
+        }
+        for ((day, part), answers) in self.wrong.iter() {
+            for answer in answers {
+                writeln!(f, "{} {} {}", day, part, answer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What happened to an answer given to [`Submitter::submit`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// Sent, and the site replied
+    Sent(Reply),
+    /// Not sent, the answers file already has a verified answer for the part
+    Verified { expected: String },
+    /// Not sent, the same answer was already found to be wrong
+    KnownWrong,
+    /// Not sent, the site is not accepting answers for this long yet
+    Cooldown(Duration),
+}
+
+/// Submits answers, keeping the answers and submissions files up to date
+#[derive(Debug, Clone)]
+pub struct Submitter {
+    client: Client,
+    answers_path: PathBuf,
+    submissions_path: PathBuf,
+}
+
+impl Submitter {
+    pub fn new(
+        client: Client,
+        answers_path: impl Into<PathBuf>,
+        submissions_path: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            client,
+            answers_path: answers_path.into(),
+            submissions_path: submissions_path.into(),
+        }
+    }
+    /// Keep the files where `loader` finds them
+    pub fn for_loader(client: Client, loader: &InputLoader) -> Self {
+        Self::new(client, Answers::path(loader), Submissions::path(loader))
+    }
+    /// Submit an answer unless it is already known to be right or wrong, or the site asked
+    /// to wait. A correct answer is added to the answers file
+    pub fn submit(&self, day: u8, part: u8, answer: &str, now: SystemTime) -> io::Result<Outcome> {
+        let answers = match std::fs::read_to_string(&self.answers_path) {
+            Ok(text) => Answers::parse(&text).map_err(invalid_data)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Answers::default(),
+            Err(err) => return Err(err),
+        };
+        if let Some(expected) = answers.get(day, part) {
+            let expected = expected.to_string();
+            return Ok(Outcome::Verified { expected });
+        }
+        let mut submissions = match std::fs::read_to_string(&self.submissions_path) {
+            Ok(text) => Submissions::parse(&text).map_err(invalid_data)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Submissions::default(),
+            Err(err) => return Err(err),
+        };
+        if submissions.is_wrong(day, part, answer) {
+            return Ok(Outcome::KnownWrong);
+        }
+        if let Some(wait) = submissions.cooldown(now) {
+            return Ok(Outcome::Cooldown(wait));
+        }
+        let reply = self.client.submit(day, part, answer)?;
+        match &reply {
+            Reply::Correct => {
+                // Append rather than rewrite, so comments in the answers file survive
+                let mut file = std::fs::OpenOptions::new()
+                    .create(true)
+                    .read(true)
+                    .append(true)
+                    .open(&self.answers_path)?;
+                if !ends_in_newline(&mut file)? {
+                    writeln!(file)?;
+                }
+                writeln!(file, "{} {} {}", day, part, answer)?;
+            }
+            Reply::Wrong { wait, .. } => {
+                submissions.record_wrong(day, part, answer);
+                if let Some(wait) = wait {
+                    submissions.wait(now, *wait);
+                }
+                std::fs::write(&self.submissions_path, submissions.to_string())?;
+            }
+            Reply::TooSoon(wait) => {
+                submissions.wait(now, *wait);
+                std::fs::write(&self.submissions_path, submissions.to_string())?;
+            }
+            Reply::AlreadySolved | Reply::Unknown(_) => {}
+        }
+        Ok(Outcome::Sent(reply))
+    }
+}
+
+/// Whether the file is empty or ends in a newline, so the next line can be appended as it is.
+/// Hand edited files often leave the newline off
+fn ends_in_newline(file: &mut std::fs::File) -> io::Result<bool> {
+    if file.metadata()?.len() == 0 {
+        return Ok(true);
+    }
+    let mut last = [0];
+    file.seek(SeekFrom::End(-1))?;
+    file.read_exact(&mut last)?;
+    Ok(last == *b"\n")
+}
+
+fn invalid_data(err: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Outcome, Submissions, Submitter};
+    use crate::site::{stand_in, Client, Reply};
+    use crate::Answers;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    const WRONG: &str = "<article><p>That's not the right answer; your answer is too high. \
+        Please wait one minute before trying again.</p></article>";
+    const RIGHT: &str = "<article><p>That's the right answer!</p></article>";
+
+    #[test]
+    fn round_trip() {
+        let text = "# mine\nwait 1700000000\n1 1 12\n1 1 13\n17 2 4,6,3\n";
+        let submissions = Submissions::parse(text).unwrap();
+        assert!(submissions.is_wrong(17, 2, "4,6,3"));
+        assert!(!submissions.is_wrong(1, 2, "12"));
+        let now = UNIX_EPOCH + Duration::from_secs(1_699_999_990);
+        assert_eq!(submissions.cooldown(now), Some(Duration::from_secs(10)));
+        let later = now + Duration::from_secs(10);
+        assert_eq!(submissions.cooldown(later), None);
+        let text = submissions.to_string();
+        assert_eq!(Submissions::parse(&text).unwrap(), submissions);
+        let err = Submissions::parse("1 3 12\n").unwrap_err();
+        assert_eq!(err.line(), Some(1));
+    }
+
+    #[test]
+    fn submit_flow() {
+        let dir = std::env::temp_dir().join(format!("advent2024-submit-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let answers_path = dir.join("answers.txt");
+        std::fs::write(&answers_path, "# verified\n1 1 11\n").unwrap();
+        let (base_url, server) = stand_in::serve(vec![(200, WRONG), (200, RIGHT)]);
+        let client = Client::new(base_url, "secret");
+        let submitter = Submitter::new(client, &answers_path, dir.join("submissions.txt"));
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        let verified = submitter.submit(1, 1, "12", now).unwrap();
+        let expected = "11".to_string();
+        assert_eq!(verified, Outcome::Verified { expected });
+        let Outcome::Sent(Reply::Wrong { hint, .. }) = submitter.submit(1, 2, "40", now).unwrap()
+        else {
+            panic!("expected a wrong answer");
+        };
+        assert_eq!(hint.as_deref(), Some("too high"));
+        // Neither of these reach the site
+        assert_eq!(
+            submitter.submit(1, 2, "40", now).unwrap(),
+            Outcome::KnownWrong
+        );
+        let soon = now + Duration::from_secs(20);
+        let cooldown = Outcome::Cooldown(Duration::from_secs(40));
+        assert_eq!(submitter.submit(1, 2, "31", soon).unwrap(), cooldown);
+        let later = now + Duration::from_secs(60);
+        let correct = Outcome::Sent(Reply::Correct);
+        assert_eq!(submitter.submit(1, 2, "31", later).unwrap(), correct);
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].method, "POST");
+        assert_eq!(requests[1].path, "/2024/day/1/answer");
+        assert_eq!(requests[1].body, "level=2&answer=31");
+        let text = std::fs::read_to_string(&answers_path).unwrap();
+        assert!(text.starts_with("# verified\n"));
+        assert_eq!(Answers::parse(&text).unwrap().get(1, 2), Some("31"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn append_after_unfinished_line() {
+        let dir = std::env::temp_dir().join(format!("advent2024-append-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let answers_path = dir.join("answers.txt");
+        // Edited by hand, without a newline at the end
+        std::fs::write(&answers_path, "1 1 11").unwrap();
+        let (base_url, server) = stand_in::serve(vec![(200, RIGHT)]);
+        let client = Client::new(base_url, "secret");
+        let submitter = Submitter::new(client, &answers_path, dir.join("submissions.txt"));
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        let correct = Outcome::Sent(Reply::Correct);
+        assert_eq!(submitter.submit(1, 2, "31", now).unwrap(), correct);
+        server.join().unwrap();
+        let text = std::fs::read_to_string(&answers_path).unwrap();
+        assert_eq!(text, "1 1 11\n1 2 31\n");
+        let answers = Answers::parse(&text).unwrap();
+        assert_eq!(answers.get(1, 1), Some("11"));
+        assert_eq!(answers.get(1, 2), Some("31"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::search::Search;
+use crate::{Grid, Result};
+
+/// Pause between frames in the terminal, unless [`Animation::delay`] changes it
+pub const DEFAULT_DELAY: Duration = Duration::from_millis(50);
+
+/// Plays a day's simulation into an [`Animation`]
+pub type Animate = fn(&str, &mut Animation) -> Result<()>;
+
+/// Foreground colors every ANSI terminal supports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Color {
+    #[default]
+    Plain,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    Gray,
+}
+
+impl Color {
+    /// Escape sequence that switches the terminal to the color
+    fn escape(self) -> &'static str {
+        match self {
+            Color::Plain => "\x1b[0m",
+            Color::Red => "\x1b[31m",
+            Color::Green => "\x1b[32m",
+            Color::Yellow => "\x1b[33m",
+            Color::Blue => "\x1b[34m",
+            Color::Magenta => "\x1b[35m",
+            Color::Cyan => "\x1b[36m",
+            Color::Gray => "\x1b[90m",
+        }
+    }
+}
+
+/// One character of a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub symbol: char,
+    pub color: Color,
+}
+
+impl Cell {
+    pub const fn new(symbol: char, color: Color) -> Self {
+        Self { symbol, color }
+    }
+    pub const fn plain(symbol: char) -> Self {
+        Self::new(symbol, Color::Plain)
+    }
+}
+
+/// A picture of a grid, with a line of text under it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    cells: Grid<Cell>,
+    caption: String,
+}
+
+impl Frame {
+    /// Draw every cell of a grid with `cell`
+    pub fn new<T>(grid: &Grid<T>, cell: impl FnMut(&T) -> Cell) -> Self {
+        Self {
+            cells: grid.map(cell),
+            caption: String::new(),
+        }
+    }
+    pub fn filled(num_rows: usize, num_cols: usize, cell: Cell) -> Self {
+        Self {
+            cells: Grid::filled(num_rows, num_cols, cell),
+            caption: String::new(),
+        }
+    }
+    /// Draw over one position, ignoring positions outside the frame
+    pub fn set(&mut self, position: (usize, usize), cell: Cell) {
+        if let Some(old) = self.cells.get_mut(position) {
+            *old = cell;
+        }
+    }
+    pub fn caption(mut self, caption: impl Into<String>) -> Self {
+        self.caption = caption.into();
+        self
+    }
+    /// The frame as text, one line per row, with ANSI colors if `colored`
+    pub fn render(&self, colored: bool) -> String {
+        let mut out = String::new();
+        for row in self.cells.rows() {
+            let mut color = Color::Plain;
+            for cell in row {
+                if colored && cell.color != color {
+                    color = cell.color;
+                    out.push_str(color.escape());
+                }
+                out.push(cell.symbol);
+            }
+            if color != Color::Plain {
+                out.push_str(Color::Plain.escape());
+            }
+            out.push('\n');
+        }
+        if !self.caption.is_empty() {
+            out.push_str(&self.caption);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Where an [`Animation`] sends its frames
+enum Output {
+    Terminal(io::Stdout),
+    File(BufWriter<File>),
+}
+
+/// Plays frames in the terminal, or writes them one after another to a text file.
+///
+/// The first output error stops the animation, and comes back from [`Animation::finish`]
+pub struct Animation {
+    output: Output,
+    delay: Duration,
+    every: usize,
+    colored: bool,
+    offered: usize,
+    shown: usize,
+    error: Option<io::Error>,
+}
+
+impl Animation {
+    /// Redraw the frames in place on stdout, in color, pausing between them
+    pub fn terminal() -> Self {
+        Self {
+            output: Output::Terminal(io::stdout()),
+            delay: DEFAULT_DELAY,
+            every: 1,
+            colored: true,
+            offered: 0,
+            shown: 0,
+            error: None,
+        }
+    }
+    /// Write the frames to a file as plain text, without pausing
+    pub fn to_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            output: Output::File(BufWriter::new(File::create(path)?)),
+            delay: Duration::ZERO,
+            colored: false,
+            ..Self::terminal()
+        })
+    }
+    /// Pause between frames, shorter to speed the animation up
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+    /// Only show every `n`th frame, for simulations with many steps
+    pub fn every(mut self, n: usize) -> Self {
+        self.every = n.max(1);
+        self
+    }
+    pub fn colored(mut self, colored: bool) -> Self {
+        self.colored = colored;
+        self
+    }
+    /// Offer the next frame, which is only drawn if [`Animation::every`] does not skip it
+    pub fn show(&mut self, frame: impl FnOnce() -> Frame) {
+        let skip = !self.offered.is_multiple_of(self.every);
+        self.offered += 1;
+        if !skip {
+            self.draw(&frame());
+        }
+    }
+    /// Draw a frame that must not be skipped, like the outcome of a simulation
+    pub fn show_always(&mut self, frame: Frame) {
+        self.offered += 1;
+        self.draw(&frame);
+    }
+    fn draw(&mut self, frame: &Frame) {
+        if self.error.is_some() {
+            return;
+        }
+        let text = frame.render(self.colored);
+        let written = match &mut self.output {
+            Output::Terminal(stdout) => {
+                // Clear the screen once, then draw over the last frame so it does not flicker
+                let home = if self.shown == 0 {
+                    "\x1b[2J\x1b[H"
+                } else {
+                    "\x1b[H"
+                };
+                write!(stdout, "{}{}\x1b[J", home, text).and_then(|()| stdout.flush())
+            }
+            Output::File(file) => writeln!(file, "{}", text),
+        };
+        match written {
+            Ok(()) => {
+                self.shown += 1;
+                std::thread::sleep(self.delay);
+            }
+            Err(err) => self.error = Some(err),
+        }
+    }
+    /// Flush the output, returning how many frames were drawn
+    pub fn finish(mut self) -> io::Result<usize> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        match &mut self.output {
+            Output::Terminal(stdout) => stdout.flush()?,
+            Output::File(file) => file.flush()?,
+        }
+        Ok(self.shown)
+    }
+}
+
+/// Grid positions reached by a search, grouped by distance from the start, nearest first.
+///
+/// Nodes that share a position, like a tile faced in different directions, count once, at
+/// the nearest of them
+pub fn waves<N: Copy + Eq + Hash>(
+    search: &Search<N>,
+    position: impl Fn(N) -> (usize, usize),
+) -> Vec<(u64, Vec<(usize, usize)>)> {
+    let mut nearest = HashMap::new();
+    for (&node, &distance) in search.distances() {
+        let best = nearest.entry(position(node)).or_insert(distance);
+        *best = distance.min(*best);
+    }
+    let mut reached: Vec<_> = nearest
+        .into_iter()
+        .map(|(position, distance)| (distance, position))
+        .collect();
+    reached.sort_unstable();
+    reached
+        .chunk_by(|a, b| a.0 == b.0)
+        .map(|wave| {
+            (
+                wave[0].0,
+                wave.iter().map(|&(_, position)| position).collect(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{waves, Animation, Cell, Color, Frame};
+    use crate::{search, Grid};
+
+    #[test]
+    fn render() {
+        let grid = Grid::new(vec![true, false, false, true], 2, 2);
+        let mut frame = Frame::new(&grid, |&wall| Cell::plain(if wall { '#' } else { '.' }));
+        frame.set((1, 0), Cell::new('@', Color::Red));
+        frame.set((5, 5), Cell::plain('x'));
+        let frame = frame.caption("step 1");
+        assert_eq!(frame.render(false), "#.\n@#\nstep 1\n");
+        assert_eq!(frame.render(true), "#.\n\x1b[31m@\x1b[0m#\nstep 1\n");
+    }
+
+    #[test]
+    fn dump_every_other_frame() {
+        let path = std::env::temp_dir().join(format!("advent2024-frames-{}", std::process::id()));
+        let mut animation = Animation::to_file(&path).unwrap().every(2);
+        for step in 0..5 {
+            animation.show(|| Frame::filled(1, 3, Cell::plain('.')).caption(step.to_string()));
+        }
+        animation.show_always(Frame::filled(1, 1, Cell::plain('@')));
+        assert_eq!(animation.finish().unwrap(), 4);
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text, "...\n0\n\n...\n2\n\n...\n4\n\n@\n\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn search_waves() {
+        // Two ways to face each cell of a row, both reached at the same distance
+        let moves = |(col, facing): (usize, bool)| {
+            [((col + 1, facing), 1), ((col, !facing), 0)]
+                .into_iter()
+                .filter(|&((col, _), _)| col < 3)
+        };
+        let search = search::dijkstra((0, false), moves, |_| false);
+        let found = waves(&search, |(col, _)| (0, col));
+        assert_eq!(
+            found,
+            [(0, vec![(0, 0)]), (1, vec![(0, 1)]), (2, vec![(0, 2)])]
+        );
+    }
+}